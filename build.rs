@@ -25,6 +25,16 @@ use std::env;
 fn main() {
     let target = env::var("TARGET").unwrap();
 
+    // Windows, macOS and the BSDs always get a backend. Elsewhere, only the
+    // `core_types` module is built if neither `std` nor a backend is enabled.
+    let needs_backend = env::var_os("CARGO_FEATURE_STD").is_some()
+        || ["windows", "darwin", "freebsd", "openbsd"]
+            .iter()
+            .any(|os| target.contains(os));
+    if !needs_backend {
+        return;
+    }
+
     if target.contains("linux") {
         compile_linux();
     } else if target.contains("windows") {
@@ -0,0 +1,296 @@
+//! Plain data types which only need `core` and `alloc`
+//!
+//! Nothing in here depends on `std` or on a backend, so these types can be
+//! shared with code that talks to HID devices over other transports.
+//! Without the default `std` feature, this module is all that the crate provides
+//! on targets where no `hidapi` C library is built by default, like Linux.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter, Result};
+use libc::wchar_t;
+
+/// The errors of [`HidError`](crate::HidError) which carry no `std` types.
+///
+/// [`HidError::as_core_error()`](crate::HidError::as_core_error) converts the
+/// other way, and every `CoreError` converts into a `HidError`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CoreError {
+    HidApiErrorEmpty,
+    InitializationError,
+    InvalidZeroSizeData,
+    IncompleteSendError {
+        sent: usize,
+        all: usize,
+    },
+    SetBlockingModeError {
+        mode: &'static str,
+    },
+    /// The device was disconnected while it was being used
+    Disconnected,
+    /// No device matched the given criteria
+    DeviceNotFound,
+    /// The operation did not finish in time
+    Timeout,
+    /// The requested backend was not compiled into this build
+    UnsupportedBackend,
+    /// The operation is not supported by the backend or the device
+    UnsupportedOperation {
+        operation: &'static str,
+    },
+    /// String indices have to fit into the `u8` of a USB string descriptor index
+    InvalidStringIndex {
+        index: i32,
+    },
+    /// The backend reported a report which is longer than the buffer it was read into
+    OversizedReport {
+        reported: usize,
+        buffer: usize,
+    },
+    /// The operation was cancelled by `HidDevice::cancel_io()`
+    Cancelled,
+    /// A `ReportReader` ran past the end of the report
+    ReportUnderrun {
+        needed: usize,
+        remaining: usize,
+    },
+    /// The device handle is not valid, e.g. because it was created from a null pointer
+    NotInitialized,
+    /// The report descriptor of the device does not declare a report with this Report ID
+    InvalidReportId {
+        report_id: u8,
+    },
+    /// The device does not provide the requested string, see [`WcharString::None`]
+    StringNotProvided,
+}
+
+impl Display for CoreError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            CoreError::HidApiErrorEmpty => write!(f, "hidapi error: (could not get error message)"),
+            CoreError::InitializationError => {
+                write!(f, "Failed to initialize hidapi")
+            }
+            CoreError::InvalidZeroSizeData => write!(f, "Invalid data: size can not be 0"),
+            CoreError::IncompleteSendError { sent, all } => {
+                write!(f, "Failed to send all data: sent {} of {} bytes", sent, all)
+            }
+            CoreError::SetBlockingModeError { mode } => {
+                write!(f, "Can not set blocking mode to '{}'", mode)
+            }
+            CoreError::Disconnected => write!(f, "Device disconnected"),
+            CoreError::DeviceNotFound => write!(f, "Device not found"),
+            CoreError::Timeout => write!(f, "Operation timed out"),
+            CoreError::UnsupportedBackend => {
+                write!(f, "The requested backend is not available in this build")
+            }
+            CoreError::UnsupportedOperation { operation } => {
+                write!(f, "{} is not supported", operation)
+            }
+            CoreError::InvalidStringIndex { index } => {
+                write!(
+                    f,
+                    "Invalid string index {}: must be between 0 and 255",
+                    index
+                )
+            }
+            CoreError::OversizedReport { reported, buffer } => write!(
+                f,
+                "Report of {} bytes does not fit into the buffer of {} bytes",
+                reported, buffer
+            ),
+            CoreError::Cancelled => write!(f, "Operation cancelled"),
+            CoreError::ReportUnderrun { needed, remaining } => write!(
+                f,
+                "Report too short: {} more bytes needed, but only {} left",
+                needed, remaining
+            ),
+            CoreError::NotInitialized => write!(f, "Device handle is not initialized"),
+            CoreError::InvalidReportId { report_id } => write!(
+                f,
+                "Report ID {:#04x} is not declared in the report descriptor",
+                report_id
+            ),
+            CoreError::StringNotProvided => write!(f, "The device does not provide this string"),
+        }
+    }
+}
+
+/// A string which was read from a device as wide characters.
+///
+/// Convert it into an `Option<String>` to get the text, if it was decoded.
+/// [`WcharString::repair()`] decodes it lossily, while the [`TryFrom`]
+/// conversion into a `String` fails if the string could not be decoded or
+/// is missing.
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum WcharString {
+    /// The decoded string
+    String(String),
+    /// The characters of a string which could not be decoded, e.g. because
+    /// of a lone UTF-16 surrogate
+    #[cfg_attr(all(feature = "linux-native", target_os = "linux"), allow(dead_code))]
+    Raw(Vec<wchar_t>),
+    /// The device did not provide the string
+    None,
+}
+
+impl WcharString {
+    /// Compare with `s`. Raw strings are decoded lossily before comparing them.
+    ///
+    /// `None` is not equal to any string, not even an empty one.
+    pub fn eq_str(&self, s: &str) -> bool {
+        match self {
+            WcharString::String(string) => string == s,
+            WcharString::Raw(raw) => wchar_to_string_lossy(raw) == s,
+            WcharString::None => false,
+        }
+    }
+
+    /// Decode the string, replacing invalid characters like lone surrogates with `U+FFFD`.
+    ///
+    /// Unlike converting into an `Option<String>`, raw strings are kept
+    /// readable. `None` is decoded as an empty string. Use [`TryFrom`] for
+    /// a conversion which fails instead.
+    pub fn repair(&self) -> String {
+        match self {
+            WcharString::String(string) => string.clone(),
+            WcharString::Raw(raw) => wchar_to_string_lossy(raw),
+            WcharString::None => String::new(),
+        }
+    }
+
+    /// The undecoded characters, if the string could not be decoded.
+    ///
+    /// The units are `wchar_t`, which is `u16` (UTF-16) on Windows and `u32`
+    /// (UTF-32) on other platforms.
+    pub fn as_raw(&self) -> Option<&[wchar_t]> {
+        match self {
+            WcharString::Raw(raw) => Some(raw),
+            _ => None,
+        }
+    }
+
+    /// The number of characters, without converting raw strings into a `String`.
+    pub fn char_len(&self) -> usize {
+        match self {
+            WcharString::String(string) => string.chars().count(),
+            WcharString::Raw(raw) => decode_wchars(raw).count(),
+            WcharString::None => 0,
+        }
+    }
+
+    /// Check whether the string starts with `prefix`, decoding raw strings only as far as needed.
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        match self {
+            WcharString::String(string) => string.starts_with(prefix),
+            WcharString::Raw(raw) => {
+                let mut chars = decode_wchars(raw);
+                prefix.chars().all(|c| chars.next() == Some(c))
+            }
+            WcharString::None => false,
+        }
+    }
+}
+
+/// Decode a wide string lazily, replacing invalid characters with `U+FFFD`
+#[cfg(target_os = "windows")]
+fn decode_wchars(raw: &[wchar_t]) -> impl Iterator<Item = char> + '_ {
+    char::decode_utf16(raw.iter().copied()).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+}
+
+/// Decode a wide string lazily, replacing invalid characters with `U+FFFD`
+#[cfg(not(target_os = "windows"))]
+fn decode_wchars(raw: &[wchar_t]) -> impl Iterator<Item = char> + '_ {
+    raw.iter()
+        .map(|&c| char::from_u32(c as u32).unwrap_or(char::REPLACEMENT_CHARACTER))
+}
+
+/// Encode a string as a null-terminated wide string
+#[cfg_attr(not(hidapi), allow(dead_code))]
+#[cfg(target_os = "windows")]
+pub(crate) fn string_to_wchars(s: &str) -> Vec<wchar_t> {
+    s.encode_utf16().chain(core::iter::once(0)).collect()
+}
+
+/// Encode a string as a null-terminated wide string
+#[cfg_attr(not(hidapi), allow(dead_code))]
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn string_to_wchars(s: &str) -> Vec<wchar_t> {
+    s.chars()
+        .map(|c| c as wchar_t)
+        .chain(core::iter::once(0))
+        .collect()
+}
+
+/// Decode a wide string, replacing invalid characters with `U+FFFD`
+pub(crate) fn wchar_to_string_lossy(raw: &[wchar_t]) -> String {
+    decode_wchars(raw).collect()
+}
+
+/// The first character which can not be decoded
+#[cfg(all(any(feature = "std", hidapi), target_os = "windows"))]
+pub(crate) fn first_invalid_wchar(raw: &[wchar_t]) -> Option<wchar_t> {
+    char::decode_utf16(raw.iter().copied())
+        .find_map(|c| c.err())
+        .map(|e| e.unpaired_surrogate() as wchar_t)
+}
+
+/// The first character which can not be decoded
+#[cfg(all(any(feature = "std", hidapi), not(target_os = "windows")))]
+pub(crate) fn first_invalid_wchar(raw: &[wchar_t]) -> Option<wchar_t> {
+    raw.iter()
+        .copied()
+        .find(|&c| char::from_u32(c as u32).is_none())
+}
+
+impl From<WcharString> for Option<String> {
+    fn from(val: WcharString) -> Self {
+        match val {
+            WcharString::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// The underlying HID bus type.
+///
+/// More bus types might be added in the future, so matches have to handle
+/// unknown ones. Bus types which this version does not know yet are kept as
+/// [`BusType::Other`] with their raw `hid_bus_type` value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BusType {
+    Unknown,
+    Usb,
+    /// Bluetooth Classic, or Bluetooth in general if the backend can not tell
+    /// Bluetooth LE apart
+    Bluetooth,
+    /// Bluetooth Low Energy (HID over GATT)
+    ///
+    /// Only reported by the `windows-native` backend, the others report
+    /// [`BusType::Bluetooth`] for these devices.
+    BluetoothLE,
+    I2c,
+    Spi,
+    Other(u8),
+}
+
+impl BusType {
+    /// Convert a raw `hid_bus_type` value of the `hidapi` C library.
+    pub fn from_raw(raw: i32) -> Self {
+        match raw {
+            0x00 => BusType::Unknown,
+            0x01 => BusType::Usb,
+            0x02 => BusType::Bluetooth,
+            0x03 => BusType::I2c,
+            0x04 => BusType::Spi,
+            raw => u8::try_from(raw).map_or(BusType::Unknown, BusType::Other),
+        }
+    }
+
+    /// Whether this is [`BusType::Bluetooth`] or [`BusType::BluetoothLE`].
+    pub fn is_bluetooth(self) -> bool {
+        matches!(self, BusType::Bluetooth | BusType::BluetoothLE)
+    }
+}
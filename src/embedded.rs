@@ -0,0 +1,74 @@
+//! An adapter for drivers written against the `embedded-io` traits
+
+use crate::{HidDevice, HidError};
+use embedded_io::{ErrorKind, ErrorType, Read, Write};
+
+impl embedded_io::Error for HidError {
+    fn kind(&self) -> ErrorKind {
+        self.io_error_kind().into()
+    }
+}
+
+/// A [`HidDevice`] which implements the [`Read`] and [`Write`] traits of `embedded-io`.
+///
+/// This allows to use the same driver for a chip on an embedded target and
+/// on a host which talks to it over HID. Every read returns one Input report,
+/// truncated to the size of the buffer, and every write sends the buffer as
+/// one Output report, so the first byte has to be the Report ID just like
+/// for [`HidDevice::write()`].
+///
+/// Only available with the `embedded-io` feature.
+#[derive(Debug)]
+pub struct EmbeddedIo {
+    device: HidDevice,
+}
+
+impl EmbeddedIo {
+    pub fn new(device: HidDevice) -> Self {
+        Self { device }
+    }
+
+    /// Get the wrapped device, e.g. to send Feature reports.
+    pub fn get_ref(&self) -> &HidDevice {
+        &self.device
+    }
+
+    pub fn into_inner(self) -> HidDevice {
+        self.device
+    }
+}
+
+impl ErrorType for EmbeddedIo {
+    type Error = HidError;
+}
+
+impl Read for EmbeddedIo {
+    /// Wait for the next Input report, regardless of the blocking mode of the device.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, HidError> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        // Returning 0 would signal the end of the stream
+        loop {
+            match self.device.read_timeout(buf, -1)? {
+                0 => continue,
+                len => return Ok(len),
+            }
+        }
+    }
+}
+
+impl Write for EmbeddedIo {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, HidError> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        // Some backends count the padding up to the report length as well
+        let written = self.device.write(buf)?;
+        Ok(written.min(buf.len()))
+    }
+
+    fn flush(&mut self) -> Result<(), HidError> {
+        Ok(())
+    }
+}
@@ -8,7 +8,8 @@ use libc::wchar_t;
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result};
 
-use crate::DeviceInfo;
+use crate::core_types::{first_invalid_wchar, wchar_to_string_lossy};
+use crate::{CoreError, DeviceInfo, WcharString};
 
 #[derive(Debug)]
 pub enum HidError {
@@ -31,43 +32,199 @@ pub enum HidError {
     OpenHidDeviceWithDeviceInfoError {
         device_info: Box<DeviceInfo>,
     },
+    /// The device was disconnected while it was being used
+    Disconnected,
+    /// No device matched the given criteria
+    DeviceNotFound,
+    /// The operation did not finish in time
+    Timeout,
+    /// The requested backend was not compiled into this build
+    UnsupportedBackend,
+    /// The operation is not supported by the backend or the device
+    UnsupportedOperation {
+        operation: &'static str,
+    },
+    /// String indices have to fit into the `u8` of a USB string descriptor index
+    InvalidStringIndex {
+        index: i32,
+    },
+    /// The backend reported a report which is longer than the buffer it was read into
+    OversizedReport {
+        reported: usize,
+        buffer: usize,
+    },
+    /// The operation was cancelled by [`HidDevice::cancel_io()`](crate::HidDevice::cancel_io)
+    Cancelled,
+    /// A [`ReportReader`](crate::ReportReader) ran past the end of the report
+    ReportUnderrun {
+        needed: usize,
+        remaining: usize,
+    },
+    /// The device handle is not valid, e.g. because it was created from a null pointer
+    NotInitialized,
+    /// The report descriptor of the device does not declare a report with this Report ID
+    InvalidReportId {
+        report_id: u8,
+    },
+    /// The device does not provide the requested string, see [`WcharString::None`]
+    StringNotProvided,
+    /// Sending the report at `index` of a batch failed
+    BatchSendFailed {
+        index: usize,
+        source: Box<HidError>,
+    },
     /// An IO error or a system error that can be represented as such
     IoError {
         error: std::io::Error,
     },
+    /// A call of the backend into the OS failed
+    ///
+    /// The error of the OS, including its code, is available through
+    /// [`Error::source()`].
+    Backend {
+        /// What the backend was doing, usually the name of the OS function which failed
+        context: &'static str,
+        error: std::io::Error,
+    },
+}
+
+impl HidError {
+    /// Get the error as a [`CoreError`], if it carries no `std` types.
+    pub fn as_core_error(&self) -> Option<CoreError> {
+        Some(match *self {
+            HidError::HidApiErrorEmpty => CoreError::HidApiErrorEmpty,
+            HidError::InitializationError => CoreError::InitializationError,
+            HidError::InvalidZeroSizeData => CoreError::InvalidZeroSizeData,
+            HidError::IncompleteSendError { sent, all } => {
+                CoreError::IncompleteSendError { sent, all }
+            }
+            HidError::SetBlockingModeError { mode } => CoreError::SetBlockingModeError { mode },
+            HidError::Disconnected => CoreError::Disconnected,
+            HidError::DeviceNotFound => CoreError::DeviceNotFound,
+            HidError::Timeout => CoreError::Timeout,
+            HidError::UnsupportedBackend => CoreError::UnsupportedBackend,
+            HidError::UnsupportedOperation { operation } => {
+                CoreError::UnsupportedOperation { operation }
+            }
+            HidError::InvalidStringIndex { index } => CoreError::InvalidStringIndex { index },
+            HidError::OversizedReport { reported, buffer } => {
+                CoreError::OversizedReport { reported, buffer }
+            }
+            HidError::Cancelled => CoreError::Cancelled,
+            HidError::ReportUnderrun { needed, remaining } => {
+                CoreError::ReportUnderrun { needed, remaining }
+            }
+            HidError::NotInitialized => CoreError::NotInitialized,
+            HidError::InvalidReportId { report_id } => CoreError::InvalidReportId { report_id },
+            HidError::StringNotProvided => CoreError::StringNotProvided,
+            HidError::HidApiError { .. }
+            | HidError::FromWideCharError { .. }
+            | HidError::OpenHidDeviceWithDeviceInfoError { .. }
+            | HidError::BatchSendFailed { .. }
+            | HidError::IoError { .. }
+            | HidError::Backend { .. } => return None,
+        })
+    }
+}
+
+impl From<CoreError> for HidError {
+    fn from(e: CoreError) -> Self {
+        match e {
+            CoreError::HidApiErrorEmpty => HidError::HidApiErrorEmpty,
+            CoreError::InitializationError => HidError::InitializationError,
+            CoreError::InvalidZeroSizeData => HidError::InvalidZeroSizeData,
+            CoreError::IncompleteSendError { sent, all } => {
+                HidError::IncompleteSendError { sent, all }
+            }
+            CoreError::SetBlockingModeError { mode } => HidError::SetBlockingModeError { mode },
+            CoreError::Disconnected => HidError::Disconnected,
+            CoreError::DeviceNotFound => HidError::DeviceNotFound,
+            CoreError::Timeout => HidError::Timeout,
+            CoreError::UnsupportedBackend => HidError::UnsupportedBackend,
+            CoreError::UnsupportedOperation { operation } => {
+                HidError::UnsupportedOperation { operation }
+            }
+            CoreError::InvalidStringIndex { index } => HidError::InvalidStringIndex { index },
+            CoreError::OversizedReport { reported, buffer } => {
+                HidError::OversizedReport { reported, buffer }
+            }
+            CoreError::Cancelled => HidError::Cancelled,
+            CoreError::ReportUnderrun { needed, remaining } => {
+                HidError::ReportUnderrun { needed, remaining }
+            }
+            CoreError::NotInitialized => HidError::NotInitialized,
+            CoreError::InvalidReportId { report_id } => HidError::InvalidReportId { report_id },
+            CoreError::StringNotProvided => HidError::StringNotProvided,
+        }
+    }
 }
 
 impl Display for HidError {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        match self {
-            HidError::HidApiError { message } => write!(f, "hidapi error: {}", message),
-            HidError::HidApiErrorEmpty => write!(f, "hidapi error: (could not get error message)"),
+        match *self {
+            HidError::HidApiError { ref message } => write!(f, "hidapi error: {}", message),
+            HidError::HidApiErrorEmpty => CoreError::HidApiErrorEmpty.fmt(f),
             HidError::FromWideCharError { wide_char } => {
                 write!(f, "failed converting {:#X} to rust char", wide_char)
             }
-            HidError::InitializationError => {
-                write!(f, "Failed to initialize hidapi")
+            HidError::InitializationError => CoreError::InitializationError.fmt(f),
+            HidError::InvalidZeroSizeData => CoreError::InvalidZeroSizeData.fmt(f),
+            HidError::IncompleteSendError { sent, all } => {
+                CoreError::IncompleteSendError { sent, all }.fmt(f)
+            }
+            HidError::SetBlockingModeError { mode } => {
+                CoreError::SetBlockingModeError { mode }.fmt(f)
             }
-            HidError::InvalidZeroSizeData => write!(f, "Invalid data: size can not be 0"),
-            HidError::IncompleteSendError { sent, all } => write!(
+            HidError::OpenHidDeviceWithDeviceInfoError { ref device_info } => write!(
                 f,
-                "Failed to send all data: only sent {} out of {} bytes",
-                sent, all
+                "Can not open hid device {:04x}:{:04x}: neither a path nor a serial number is available",
+                device_info.vendor_id(),
+                device_info.product_id()
             ),
-            HidError::SetBlockingModeError { mode } => {
-                write!(f, "Can not set blocking mode to '{}'", mode)
+            HidError::Disconnected => CoreError::Disconnected.fmt(f),
+            HidError::DeviceNotFound => CoreError::DeviceNotFound.fmt(f),
+            HidError::Timeout => CoreError::Timeout.fmt(f),
+            HidError::UnsupportedBackend => CoreError::UnsupportedBackend.fmt(f),
+            HidError::UnsupportedOperation { operation } => {
+                CoreError::UnsupportedOperation { operation }.fmt(f)
+            }
+            HidError::InvalidStringIndex { index } => CoreError::InvalidStringIndex { index }.fmt(f),
+            HidError::OversizedReport { reported, buffer } => {
+                CoreError::OversizedReport { reported, buffer }.fmt(f)
+            }
+            HidError::Cancelled => CoreError::Cancelled.fmt(f),
+            HidError::ReportUnderrun { needed, remaining } => {
+                CoreError::ReportUnderrun { needed, remaining }.fmt(f)
             }
-            HidError::OpenHidDeviceWithDeviceInfoError { device_info } => {
-                write!(f, "Can not open hid device with: {:?}", *device_info)
+            HidError::NotInitialized => CoreError::NotInitialized.fmt(f),
+            HidError::InvalidReportId { report_id } => {
+                CoreError::InvalidReportId { report_id }.fmt(f)
             }
-            HidError::IoError { error } => {
+            HidError::StringNotProvided => CoreError::StringNotProvided.fmt(f),
+            // The cause is available through `Error::source()`
+            HidError::BatchSendFailed { index, .. } => {
+                write!(f, "Failed to send report {} of the batch", index)
+            }
+            HidError::IoError { ref error } => {
                 write!(f, "{error}")
             }
+            // The error of the OS is available through `Error::source()`
+            HidError::Backend { context, .. } => write!(f, "{context} failed"),
         }
     }
 }
 
-impl Error for HidError {}
+impl Error for HidError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HidError::BatchSendFailed { source, .. } => Some(source.as_ref()),
+            // The IO error is displayed as it is, so skip it in the chain as well
+            HidError::IoError { error } => error.source(),
+            HidError::Backend { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
 
 impl From<std::io::Error> for HidError {
     fn from(e: std::io::Error) -> Self {
@@ -75,6 +232,58 @@ impl From<std::io::Error> for HidError {
     }
 }
 
+/// Fails with [`HidError::FromWideCharError`] for the first character of a
+/// string which could not be decoded, and with [`HidError::StringNotProvided`]
+/// if the string is missing.
+///
+/// There is no lossy `From<WcharString> for String`, because it would come with
+/// a blanket `TryFrom` which can not fail and conflicts with this one. Use
+/// [`WcharString::repair()`] to decode a string lossily instead.
+impl TryFrom<WcharString> for String {
+    type Error = HidError;
+
+    fn try_from(val: WcharString) -> std::result::Result<Self, HidError> {
+        match val {
+            WcharString::String(s) => Ok(s),
+            WcharString::Raw(raw) => match first_invalid_wchar(&raw) {
+                Some(wide_char) => Err(HidError::FromWideCharError { wide_char }),
+                None => Ok(wchar_to_string_lossy(&raw)),
+            },
+            WcharString::None => Err(HidError::StringNotProvided),
+        }
+    }
+}
+
+impl HidError {
+    /// The kind of the [`std::io::Error`] this error converts into.
+    pub(crate) fn io_error_kind(&self) -> std::io::ErrorKind {
+        use std::io::ErrorKind;
+
+        match self {
+            HidError::IoError { error } | HidError::Backend { error, .. } => error.kind(),
+            HidError::InvalidZeroSizeData | HidError::InvalidReportId { .. } => {
+                ErrorKind::InvalidInput
+            }
+            HidError::IncompleteSendError { .. } => ErrorKind::WriteZero,
+            HidError::Disconnected => ErrorKind::BrokenPipe,
+            HidError::Timeout => ErrorKind::TimedOut,
+            HidError::Cancelled => ErrorKind::Interrupted,
+            HidError::ReportUnderrun { .. } => ErrorKind::UnexpectedEof,
+            HidError::UnsupportedOperation { .. } => ErrorKind::Unsupported,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl From<HidError> for std::io::Error {
+    fn from(e: HidError) -> Self {
+        if let HidError::IoError { error } = e {
+            return error;
+        }
+        std::io::Error::new(e.io_error_kind(), e)
+    }
+}
+
 #[cfg(all(feature = "linux-native", target_os = "linux"))]
 impl From<nix::errno::Errno> for HidError {
     fn from(e: nix::errno::Errno) -> Self {
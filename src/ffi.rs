@@ -7,7 +7,8 @@
 /// *************************************************************************
 // For documentation look at the corresponding C header file hidapi.h
 use libc::{c_char, c_int, c_uchar, c_ushort, c_void, intptr_t, size_t, wchar_t};
-type HidBusType = crate::BusType;
+/// The raw value of `hid_bus_type`, see [`crate::BusType::from_raw()`]
+type HidBusType = c_int;
 pub type HidDevice = c_void;
 type LibusbContext = c_void;
 
@@ -27,6 +28,13 @@ pub struct HidDeviceInfo {
     pub bus_type: HidBusType,
 }
 
+#[repr(C)]
+pub struct HidApiVersion {
+    pub major: c_int,
+    pub minor: c_int,
+    pub patch: c_int,
+}
+
 #[allow(dead_code)]
 extern "C" {
     #[cfg_attr(target_os = "openbsd", link_name = "hidapi_hid_init")]
@@ -92,6 +100,8 @@ extern "C" {
         buf_size: size_t,
     ) -> c_int;
     pub fn hid_error(device: *mut HidDevice) -> *const wchar_t;
+    pub fn hid_version() -> *const HidApiVersion;
+    pub fn hid_version_str() -> *const c_char;
 }
 
 // For documentation look at the corresponding C header file hidapi_darwin.h
@@ -3,11 +3,15 @@
 use std::{
     ffi::CStr,
     fmt::{self, Debug},
+    sync::Mutex,
 };
 
 use libc::{c_int, size_t, wchar_t};
 
-use crate::{ffi, DeviceInfo, HidDeviceBackendBase, HidError, HidResult, WcharString};
+use crate::core_types::string_to_wchars;
+use crate::{
+    ffi, Access, BusType, DeviceInfo, HidDeviceBackendBase, HidError, HidResult, WcharString,
+};
 
 #[cfg(target_os = "macos")]
 mod macos;
@@ -16,18 +20,59 @@ mod windows;
 
 const STRING_BUF_LEN: usize = 128;
 
+/// Serializes the calls which report their errors through `hid_error(NULL)`,
+/// and the enumerations, which the C library does not allow in parallel to them
+static GLOBAL_ERROR_LOCK: Mutex<()> = Mutex::new(());
+
+/// Trace a call into the C library, if the `log` feature is enabled
+macro_rules! trace_ffi {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::trace!($($arg)*);
+    };
+}
+
 pub struct HidApiBackend;
 
 impl HidApiBackend {
     pub fn get_hid_device_info_vector(vid: u16, pid: u16) -> HidResult<Vec<DeviceInfo>> {
         let mut device_vector = Vec::with_capacity(8);
+        Self::enumerate_with(vid, pid, |info| device_vector.push(info))?;
+        Ok(device_vector)
+    }
+
+    pub fn get_hid_device_info_vector_for_bus(bus: BusType) -> HidResult<Vec<DeviceInfo>> {
+        let mut devices = Vec::new();
+        Self::enumerate_with(0, 0, |info| {
+            if info.bus_type == bus {
+                devices.push(info)
+            }
+        })?;
+        Ok(devices)
+    }
 
+    pub fn enumerate_with(vid: u16, pid: u16, mut f: impl FnMut(DeviceInfo)) -> HidResult<()> {
+        // Enumerations can run on a worker thread, see `HidApi::enumerate_timeout()`
+        let _guard = GLOBAL_ERROR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         let enumeration = unsafe { ffi::hid_enumerate(vid, pid) };
+        trace_ffi!(
+            "hid_enumerate({:#06x}, {:#06x}) = {:?}",
+            vid,
+            pid,
+            enumeration
+        );
+        let mut res = Ok(());
         {
             let mut current_device = enumeration;
 
             while !current_device.is_null() {
-                device_vector.push(unsafe { conv_hid_device_info(current_device)? });
+                match unsafe { conv_hid_device_info(current_device) } {
+                    Ok(info) => f(info),
+                    Err(e) => {
+                        res = Err(e);
+                        break;
+                    }
+                }
                 current_device = unsafe { (*current_device).next };
             }
         }
@@ -36,49 +81,64 @@ impl HidApiBackend {
             unsafe { ffi::hid_free_enumeration(enumeration) };
         }
 
-        Ok(device_vector)
+        res
     }
 
-    pub fn open(vid: u16, pid: u16) -> HidResult<HidDevice> {
-        let device = unsafe { ffi::hid_open(vid, pid, std::ptr::null()) };
+    pub fn open_serial(vid: u16, pid: u16, sn: &str) -> HidResult<HidDevice> {
+        let chars = string_to_wchars(sn);
+        Self::open_with(|| {
+            let device = unsafe { ffi::hid_open(vid, pid, chars.as_ptr()) };
+            trace_ffi!(
+                "hid_open({:#06x}, {:#06x}, {:?}) = {:?}",
+                vid,
+                pid,
+                sn,
+                device
+            );
+            device
+        })
+    }
 
-        if device.is_null() {
-            match Self::check_error() {
-                Ok(err) => Err(err),
-                Err(e) => Err(e),
-            }
-        } else {
-            Ok(HidDevice::from_raw(device))
-        }
+    pub fn open_path(device_path: &CStr) -> HidResult<HidDevice> {
+        Self::open_with(|| {
+            let device = unsafe { ffi::hid_open_path(device_path.as_ptr()) };
+            trace_ffi!("hid_open_path({:?}) = {:?}", device_path, device);
+            device
+        })
     }
 
-    pub fn open_serial(vid: u16, pid: u16, sn: &str) -> HidResult<HidDevice> {
-        let mut chars = sn.chars().map(|c| c as wchar_t).collect::<Vec<_>>();
-        chars.push(0 as wchar_t);
-        let device = unsafe { ffi::hid_open(vid, pid, chars.as_ptr()) };
-        if device.is_null() {
-            match Self::check_error() {
-                Ok(err) => Err(err),
-                Err(e) => Err(e),
-            }
-        } else {
-            Ok(HidDevice::from_raw(device))
+    pub fn open_path_with_access(device_path: &CStr, access: Access) -> HidResult<HidDevice> {
+        match access {
+            Access::ReadWrite => Self::open_path(device_path),
+            Access::Read | Access::Write => Err(HidError::UnsupportedOperation {
+                operation: "open_path_with_access",
+            }),
         }
     }
 
-    pub fn open_path(device_path: &CStr) -> HidResult<HidDevice> {
-        let device = unsafe { ffi::hid_open_path(device_path.as_ptr()) };
-
+    /// Call `open` and turn a null device into the error of the C library.
+    ///
+    /// Failures to open a device are only reported through the global error of
+    /// `hid_error(NULL)`, which every failing call on any thread overwrites. So
+    /// the lock is held from the call until the message has been copied.
+    pub fn open_with(open: impl FnOnce() -> *mut ffi::HidDevice) -> HidResult<HidDevice> {
+        let _guard = GLOBAL_ERROR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let device = open();
         if device.is_null() {
             match Self::check_error() {
                 Ok(err) => Err(err),
                 Err(e) => Err(e),
             }
         } else {
-            Ok(HidDevice::from_raw(device))
+            HidDevice::from_raw(device)
         }
     }
 
+    /// Get the last global error of the C library.
+    ///
+    /// The global error is shared by all threads, so this is racy unless
+    /// [`GLOBAL_ERROR_LOCK`] is held since the failing call. The errors of an
+    /// open device are kept per device and can be queried without a lock.
     pub fn check_error() -> HidResult<HidError> {
         Ok(HidError::HidApiError {
             message: unsafe {
@@ -89,6 +149,23 @@ impl HidApiBackend {
             },
         })
     }
+
+    pub fn library_version() -> (u16, u16, u16) {
+        // The version is a static struct of the library, which is valid even
+        // before hid_init()
+        let version = unsafe { &*ffi::hid_version() };
+        (
+            version.major as u16,
+            version.minor as u16,
+            version.patch as u16,
+        )
+    }
+
+    pub fn library_version_str() -> String {
+        unsafe { CStr::from_ptr(ffi::hid_version_str()) }
+            .to_string_lossy()
+            .into_owned()
+    }
 }
 
 /// Converts a pointer to a `*const wchar_t` to a WcharString.
@@ -129,8 +206,17 @@ unsafe fn wchar_to_string(wstr: *const wchar_t) -> WcharString {
 
 /// Convert the CFFI `HidDeviceInfo` struct to a native `HidDeviceInfo` struct
 pub unsafe fn conv_hid_device_info(src: *mut ffi::HidDeviceInfo) -> HidResult<DeviceInfo> {
+    let path = CStr::from_ptr((*src).path).to_owned();
+    let bus_type = BusType::from_raw((*src).bus_type);
+    // Older versions of the C library do not report the bus type on Windows
+    #[cfg(target_os = "windows")]
+    let bus_type = match bus_type {
+        BusType::Unknown => windows::bus_type_from_path(&path),
+        bus_type => bus_type,
+    };
+
     Ok(DeviceInfo {
-        path: CStr::from_ptr((*src).path).to_owned(),
+        path,
         vendor_id: (*src).vendor_id,
         product_id: (*src).product_id,
         serial_number: wchar_to_string((*src).serial_number),
@@ -139,8 +225,22 @@ pub unsafe fn conv_hid_device_info(src: *mut ffi::HidDeviceInfo) -> HidResult<De
         product_string: wchar_to_string((*src).product_string),
         usage_page: (*src).usage_page,
         usage: (*src).usage,
+        // The libusb backend on linux does not report the usage
+        usage_known: !cfg!(all(libusb, target_os = "linux")),
         interface_number: (*src).interface_number,
-        bus_type: (*src).bus_type,
+        bus_type,
+        input_report_length: None,
+        output_report_length: None,
+        feature_report_length: None,
+        usb_bcd_device: None,
+        present: true,
+        friendly_name: None,
+        #[cfg(target_os = "windows")]
+        container_id: None,
+        #[cfg(target_os = "windows")]
+        parent_instance_id: None,
+        #[cfg(target_os = "windows")]
+        instance_id: None,
     })
 }
 
@@ -150,9 +250,35 @@ pub struct HidDevice {
 }
 
 impl HidDevice {
-    pub fn from_raw(device: *mut ffi::HidDevice) -> Self {
-        Self {
+    /// Take ownership of a device opened by the C library.
+    ///
+    /// Fails with [`HidError::NotInitialized`] if `device` is null.
+    pub fn from_raw(device: *mut ffi::HidDevice) -> HidResult<Self> {
+        if device.is_null() {
+            return Err(HidError::NotInitialized);
+        }
+        Ok(Self {
             _hid_device: device,
+        })
+    }
+
+    /// Close the handle, unless it is closed already. Used by both `close()` and `Drop`.
+    fn close_handle(&mut self) {
+        let device = std::mem::replace(&mut self._hid_device, std::ptr::null_mut());
+        if !device.is_null() {
+            unsafe { ffi::hid_close(device) }
+            trace_ffi!("hid_close({:?})", device);
+        }
+    }
+
+    /// Get the handle for a call into the C library.
+    ///
+    /// Passing a null handle to the C library crashes it, so check it first.
+    fn handle(&self) -> HidResult<*mut ffi::HidDevice> {
+        if self._hid_device.is_null() {
+            Err(HidError::NotInitialized)
+        } else {
+            Ok(self._hid_device)
         }
     }
 }
@@ -167,7 +293,7 @@ impl Debug for HidDevice {
 
 impl Drop for HidDevice {
     fn drop(&mut self) {
-        unsafe { ffi::hid_close(self._hid_device) }
+        self.close_handle();
     }
 }
 
@@ -199,37 +325,50 @@ impl HidDeviceBackendBase for HidDevice {
     }
 
     fn write(&self, data: &[u8]) -> HidResult<usize> {
+        let device = self.handle()?;
         if data.is_empty() {
             return Err(HidError::InvalidZeroSizeData);
         }
-        let res = unsafe { ffi::hid_write(self._hid_device, data.as_ptr(), data.len() as size_t) };
+        let res = unsafe { ffi::hid_write(device, data.as_ptr(), data.len() as size_t) };
+        trace_ffi!("hid_write({:?}, {} bytes) = {}", device, data.len(), res);
         self.check_size(res)
     }
 
     fn read(&self, buf: &mut [u8]) -> HidResult<usize> {
-        let res = unsafe { ffi::hid_read(self._hid_device, buf.as_mut_ptr(), buf.len() as size_t) };
+        let device = self.handle()?;
+        let res = unsafe { ffi::hid_read(device, buf.as_mut_ptr(), buf.len() as size_t) };
+        trace_ffi!("hid_read({:?}, {} bytes) = {}", device, buf.len(), res);
         self.check_size(res)
     }
 
     fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> HidResult<usize> {
+        let device = self.handle()?;
         let res = unsafe {
-            ffi::hid_read_timeout(
-                self._hid_device,
-                buf.as_mut_ptr(),
-                buf.len() as size_t,
-                timeout,
-            )
+            ffi::hid_read_timeout(device, buf.as_mut_ptr(), buf.len() as size_t, timeout)
         };
+        trace_ffi!(
+            "hid_read_timeout({:?}, {} bytes, {}) = {}",
+            device,
+            buf.len(),
+            timeout,
+            res
+        );
         self.check_size(res)
     }
 
     fn send_feature_report(&self, data: &[u8]) -> HidResult<()> {
+        let device = self.handle()?;
         if data.is_empty() {
             return Err(HidError::InvalidZeroSizeData);
         }
-        let res = unsafe {
-            ffi::hid_send_feature_report(self._hid_device, data.as_ptr(), data.len() as size_t)
-        };
+        let res =
+            unsafe { ffi::hid_send_feature_report(device, data.as_ptr(), data.len() as size_t) };
+        trace_ffi!(
+            "hid_send_feature_report({:?}, {} bytes) = {}",
+            device,
+            data.len(),
+            res
+        );
         let res = self.check_size(res)?;
         if res != data.len() {
             Err(HidError::IncompleteSendError {
@@ -245,16 +384,22 @@ impl HidDeviceBackendBase for HidDevice {
     /// Upon return, the first byte will still contain the Report ID, and the
     /// report data will start in `buf[1]`.
     fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
-        let res = unsafe {
-            ffi::hid_get_feature_report(self._hid_device, buf.as_mut_ptr(), buf.len() as size_t)
-        };
+        let device = self.handle()?;
+        let res =
+            unsafe { ffi::hid_get_feature_report(device, buf.as_mut_ptr(), buf.len() as size_t) };
+        trace_ffi!(
+            "hid_get_feature_report({:?}, {} bytes) = {}",
+            device,
+            buf.len(),
+            res
+        );
         self.check_size(res)
     }
 
     fn set_blocking_mode(&self, blocking: bool) -> HidResult<()> {
-        let res = unsafe {
-            ffi::hid_set_nonblocking(self._hid_device, if blocking { 0i32 } else { 1i32 })
-        };
+        let device = self.handle()?;
+        let res = unsafe { ffi::hid_set_nonblocking(device, if blocking { 0i32 } else { 1i32 }) };
+        trace_ffi!("hid_set_nonblocking({:?}, {}) = {}", device, !blocking, res);
         if res == -1 {
             Err(HidError::SetBlockingModeError {
                 mode: match blocking {
@@ -268,60 +413,53 @@ impl HidDeviceBackendBase for HidDevice {
     }
 
     fn get_manufacturer_string(&self) -> HidResult<Option<String>> {
+        let device = self.handle()?;
         let mut buf = [0 as wchar_t; STRING_BUF_LEN];
         let res = unsafe {
-            ffi::hid_get_manufacturer_string(
-                self._hid_device,
-                buf.as_mut_ptr(),
-                STRING_BUF_LEN as size_t,
-            )
+            ffi::hid_get_manufacturer_string(device, buf.as_mut_ptr(), STRING_BUF_LEN as size_t)
         };
+        trace_ffi!("hid_get_manufacturer_string({:?}) = {}", device, res);
         let res = self.check_size(res)?;
         unsafe { Ok(wchar_to_string(buf[..res].as_ptr()).into()) }
     }
 
     fn get_product_string(&self) -> HidResult<Option<String>> {
+        let device = self.handle()?;
         let mut buf = [0 as wchar_t; STRING_BUF_LEN];
         let res = unsafe {
-            ffi::hid_get_product_string(
-                self._hid_device,
-                buf.as_mut_ptr(),
-                STRING_BUF_LEN as size_t,
-            )
+            ffi::hid_get_product_string(device, buf.as_mut_ptr(), STRING_BUF_LEN as size_t)
         };
+        trace_ffi!("hid_get_product_string({:?}) = {}", device, res);
         let res = self.check_size(res)?;
         unsafe { Ok(wchar_to_string(buf[..res].as_ptr()).into()) }
     }
 
     fn get_serial_number_string(&self) -> HidResult<Option<String>> {
+        let device = self.handle()?;
         let mut buf = [0 as wchar_t; STRING_BUF_LEN];
         let res = unsafe {
-            ffi::hid_get_serial_number_string(
-                self._hid_device,
-                buf.as_mut_ptr(),
-                STRING_BUF_LEN as size_t,
-            )
+            ffi::hid_get_serial_number_string(device, buf.as_mut_ptr(), STRING_BUF_LEN as size_t)
         };
+        trace_ffi!("hid_get_serial_number_string({:?}) = {}", device, res);
         let res = self.check_size(res)?;
         unsafe { Ok(wchar_to_string(buf[..res].as_ptr()).into()) }
     }
 
     fn get_indexed_string(&self, index: i32) -> HidResult<Option<String>> {
+        let device = self.handle()?;
         let mut buf = [0 as wchar_t; STRING_BUF_LEN];
         let res = unsafe {
-            ffi::hid_get_indexed_string(
-                self._hid_device,
-                index as c_int,
-                buf.as_mut_ptr(),
-                STRING_BUF_LEN,
-            )
+            ffi::hid_get_indexed_string(device, index as c_int, buf.as_mut_ptr(), STRING_BUF_LEN)
         };
+        trace_ffi!("hid_get_indexed_string({:?}, {}) = {}", device, index, res);
         let res = self.check_size(res)?;
         unsafe { Ok(wchar_to_string(buf[..res].as_ptr()).into()) }
     }
 
     fn get_device_info(&self) -> HidResult<DeviceInfo> {
-        let raw_device = unsafe { ffi::hid_get_device_info(self._hid_device) };
+        let device = self.handle()?;
+        let raw_device = unsafe { ffi::hid_get_device_info(device) };
+        trace_ffi!("hid_get_device_info({:?}) = {:?}", device, raw_device);
         if raw_device.is_null() {
             match self.check_error() {
                 Ok(err) | Err(err) => return Err(err),
@@ -332,9 +470,20 @@ impl HidDeviceBackendBase for HidDevice {
     }
 
     fn get_report_descriptor(&self, buf: &mut [u8]) -> HidResult<usize> {
-        let res = unsafe {
-            ffi::hid_get_report_descriptor(self._hid_device, buf.as_mut_ptr(), buf.len())
-        };
+        let device = self.handle()?;
+        let res = unsafe { ffi::hid_get_report_descriptor(device, buf.as_mut_ptr(), buf.len()) };
+        trace_ffi!(
+            "hid_get_report_descriptor({:?}, {} bytes) = {}",
+            device,
+            buf.len(),
+            res
+        );
         self.check_size(res)
     }
+
+    fn close(mut self: Box<Self>) -> HidResult<()> {
+        // `hid_close()` does not report errors
+        self.close_handle();
+        Ok(())
+    }
 }
@@ -5,11 +5,11 @@ use crate::{ffi, HidDeviceBackendBase, HidDeviceBackendMacos, HidResult};
 
 impl HidDeviceBackendMacos for HidDevice {
     fn get_location_id(&self) -> HidResult<u32> {
+        let device = self.handle()?;
         let mut location_id: u32 = 0;
 
-        let res = unsafe {
-            ffi::macos::hid_darwin_get_location_id(self._hid_device, &mut location_id as *mut u32)
-        };
+        let res =
+            unsafe { ffi::macos::hid_darwin_get_location_id(device, &mut location_id as *mut u32) };
 
         if res == -1 {
             match self.check_error() {
@@ -22,7 +22,8 @@ impl HidDeviceBackendMacos for HidDevice {
     }
 
     fn is_open_exclusive(&self) -> HidResult<bool> {
-        let res = unsafe { ffi::macos::hid_darwin_is_device_open_exclusive(self._hid_device) };
+        let device = self.handle()?;
+        let res = unsafe { ffi::macos::hid_darwin_is_device_open_exclusive(device) };
 
         if res == -1 {
             match self.check_error() {
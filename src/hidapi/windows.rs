@@ -1,17 +1,114 @@
 //! The extra behaviour for Windows
 
-use std::ptr::addr_of_mut;
+use std::ffi::CStr;
+use std::ptr::{addr_of_mut, null_mut};
 use windows_sys::core::GUID;
+use windows_sys::Win32::Devices::DeviceAndDriverInstallation::{
+    CM_Get_DevNode_PropertyW, CM_Get_Device_Interface_PropertyW, CM_Get_Parent, CM_Locate_DevNodeW,
+    CM_LOCATE_DEVNODE_NORMAL, CONFIGRET, CR_BUFFER_SMALL, CR_SUCCESS,
+};
+use windows_sys::Win32::Devices::Properties::{
+    DEVPKEY_Device_CompatibleIds, DEVPKEY_Device_InstanceId, DEVPROPTYPE, DEVPROP_TYPE_STRING,
+    DEVPROP_TYPE_STRING_LIST,
+};
 
 use super::HidDevice;
-use crate::{ffi, HidDeviceBackendBase, HidDeviceBackendWindows, HidResult};
+use crate::{ffi, BusType, HidDeviceBackendBase, HidDeviceBackendWindows, HidResult};
+
+/// Get the bus type from the device node of the device interface at `path`.
+///
+/// Older versions of the C library do not report a bus type at all. Like the
+/// `windows-native` backend, this looks at the compatible IDs of the parent
+/// of the HID device node, so that both backends report the same bus types.
+pub fn bus_type_from_path(path: &CStr) -> BusType {
+    get_bus_type(path).unwrap_or(BusType::Unknown)
+}
+
+fn get_bus_type(path: &CStr) -> Option<BusType> {
+    let interface: Vec<u16> = path.to_str().ok()?.encode_utf16().chain([0]).collect();
+    let device_id = get_string_property(DEVPROP_TYPE_STRING, |property_type, buf, len| unsafe {
+        CM_Get_Device_Interface_PropertyW(
+            interface.as_ptr(),
+            &DEVPKEY_Device_InstanceId,
+            property_type,
+            buf,
+            len,
+            0,
+        )
+    })?;
+
+    let mut node = 0;
+    let cr = unsafe { CM_Locate_DevNodeW(&mut node, device_id.as_ptr(), CM_LOCATE_DEVNODE_NORMAL) };
+    if cr != CR_SUCCESS {
+        return None;
+    }
+    let mut parent = 0;
+    let cr = unsafe { CM_Get_Parent(&mut parent, node, 0) };
+    if cr != CR_SUCCESS {
+        return None;
+    }
+
+    let compatible_ids =
+        get_string_property(DEVPROP_TYPE_STRING_LIST, |property_type, buf, len| unsafe {
+            CM_Get_DevNode_PropertyW(
+                parent,
+                &DEVPKEY_Device_CompatibleIds,
+                property_type,
+                buf,
+                len,
+                0,
+            )
+        })?;
+    compatible_ids
+        .split(|&c| c == 0)
+        .find_map(bus_type_from_compatible_id)
+}
+
+/// Read a string property of type `expected` with `get`, which takes the
+/// property type, buffer and buffer size like the `CM_Get_*_PropertyW` functions.
+fn get_string_property(
+    expected: DEVPROPTYPE,
+    get: impl Fn(*mut DEVPROPTYPE, *mut u8, *mut u32) -> CONFIGRET,
+) -> Option<Vec<u16>> {
+    let mut property_type = 0;
+    let mut len = 0;
+    let cr = get(&mut property_type, null_mut(), &mut len);
+    if cr != CR_BUFFER_SMALL || property_type != expected {
+        return None;
+    }
+
+    let mut buf = vec![0u16; (len as usize).div_ceil(2)];
+    let cr = get(&mut property_type, buf.as_mut_ptr().cast(), &mut len);
+    (cr == CR_SUCCESS).then_some(buf)
+}
+
+/// The same compatible IDs as `get_bus` of the `windows-native` backend
+fn bus_type_from_compatible_id(id: &[u16]) -> Option<BusType> {
+    let starts_with = |prefix: &str| {
+        id.len() >= prefix.len()
+            && id
+                .iter()
+                .zip(prefix.bytes())
+                .all(|(&c, p)| u8::try_from(c).is_ok_and(|c| c.eq_ignore_ascii_case(&p)))
+    };
+    [
+        ("USB", BusType::Usb),
+        ("BTHENUM", BusType::Bluetooth),
+        ("BTHLEDEVICE", BusType::BluetoothLE),
+        ("PNP0C50", BusType::I2c),
+        ("PNP0C51", BusType::Spi),
+    ]
+    .into_iter()
+    .find_map(|(prefix, bus_type)| starts_with(prefix).then_some(bus_type))
+}
 
 impl HidDeviceBackendWindows for HidDevice {
     fn get_container_id(&self) -> HidResult<GUID> {
+        let device = self.handle()?;
         let mut container_id: GUID = unsafe { std::mem::zeroed() };
 
         let res = unsafe {
-            ffi::windows::hid_winapi_get_container_id(self._hid_device, addr_of_mut!(container_id))
+            ffi::windows::hid_winapi_get_container_id(device, addr_of_mut!(container_id))
         };
 
         if res == -1 {
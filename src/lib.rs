@@ -39,6 +39,9 @@
 //!
 //! # Feature flags
 //!
+//! - `std`: everything but [`core_types`], which only needs `core` and `alloc` (default, and implied
+//!   by all of the backend features). On Windows, macOS, FreeBSD and OpenBSD the `hidapi` C library
+//!   is always built, so there the whole crate is available even without this feature.
 //! - `linux-static-libusb`: uses statically linked `libusb` backend on Linux
 //! - `linux-static-hidraw`: uses statically linked `hidraw` backend on Linux (default)
 //! - `linux-shared-libusb`: uses dynamically linked `libusb` backend on Linux
@@ -48,6 +51,9 @@
 //! - `illumos-shared-libusb`: uses statically linked `hidraw` backend on Illumos
 //! - `macos-shared-device`: enables shared access to HID devices on MacOS
 //! - `windows-native`: talks to hid.dll directly without using the `hidapi` C library
+//! - `embedded-io`: implements the [`embedded-io`](https://docs.rs/embedded-io) traits for
+//!   [`HidDevice`] through `EmbeddedIo`
+//! - `log`: traces the calls into the `hidapi` C library with the [`log`](https://docs.rs/log) crate
 //!
 //! ## Linux backends
 //!
@@ -60,19 +66,61 @@
 //! [`HidDevice`] handles can access the same physical device. For backward compatibility this is
 //! an opt-in that can be enabled with the `macos-shared-device` feature flag.
 
+#![cfg_attr(not(any(feature = "std", hidapi)), no_std)]
+
+extern crate alloc;
+
+pub mod core_types;
+#[cfg(feature = "embedded-io")]
+mod embedded;
+#[cfg(any(feature = "std", hidapi))]
 mod error;
+#[cfg(any(feature = "std", hidapi))]
 mod ffi;
-
+#[cfg(any(feature = "std", hidapi))]
+mod pool;
+#[cfg(any(feature = "std", hidapi))]
+mod report;
+#[cfg(all(feature = "std", not(hidapi)))]
+mod split;
+#[cfg(any(feature = "std", hidapi))]
+mod usage;
+
+#[cfg(any(feature = "std", hidapi))]
 use cfg_if::cfg_if;
+#[cfg(any(feature = "std", hidapi))]
 use libc::wchar_t;
-use std::ffi::CStr;
-use std::ffi::CString;
-use std::fmt;
-use std::fmt::Debug;
-use std::sync::Mutex;
-
+#[cfg(any(feature = "std", hidapi))]
+use std::{
+    borrow::Cow,
+    cell::{Cell, OnceCell},
+    ffi::{CStr, CString},
+    fmt,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    sync::mpsc::{self, RecvTimeoutError},
+    sync::{Arc, Mutex, RwLock},
+    thread,
+    time::{Duration, Instant},
+};
+
+pub use core_types::{BusType, CoreError, WcharString};
+#[cfg(feature = "embedded-io")]
+pub use embedded::EmbeddedIo;
+#[cfg(any(feature = "std", hidapi))]
 pub use error::HidError;
-
+#[cfg(any(feature = "std", hidapi))]
+pub use pool::{PooledReport, ReportPool};
+#[cfg(any(feature = "std", hidapi))]
+use report::ReportIds;
+#[cfg(any(feature = "std", hidapi))]
+pub use report::ReportReader;
+#[cfg(all(feature = "std", not(hidapi)))]
+pub use split::{HidReader, HidWriter};
+#[cfg(any(feature = "std", hidapi))]
+pub use usage::{Usage, UsagePage};
+
+#[cfg(any(feature = "std", hidapi))]
 cfg_if! {
     if #[cfg(all(feature = "linux-native", target_os = "linux"))] {
         //#[cfg_attr(docsrs, doc(cfg(all(feature = "linux-native", target_os = "linux"))))]
@@ -91,15 +139,91 @@ cfg_if! {
 }
 
 // Automatically implement the top trait
+#[cfg(any(feature = "std", hidapi))]
 cfg_if! {
     if #[cfg(target_os = "windows")] {
         #[cfg_attr(docsrs, doc(cfg(target_os = "windows")))]
         mod windows;
         use windows::GUID;
+        pub use windows::{guid_eq, guid_to_string, ReadOperation};
+        #[cfg(feature = "windows-native")]
+        pub use windows::{AccessState, EnumStats, PhysicalDevice};
+        #[cfg(feature = "windows-native")]
+        pub use windows_native::UsbInstanceId;
         /// A trait with the extra methods that are available on Windows
         trait HidDeviceBackendWindows {
             /// Get the container ID for a HID device
             fn get_container_id(&self) -> HidResult<GUID>;
+
+            /// Set the number of Input reports the HID class driver buffers
+            fn set_input_buffer_count(&self, _count: u32) -> HidResult<()> {
+                Err(HidError::UnsupportedOperation {
+                    operation: "set_input_buffer_count",
+                })
+            }
+
+            /// Get the number of Input reports the HID class driver buffers
+            fn get_input_buffer_count(&self) -> HidResult<u32> {
+                Err(HidError::UnsupportedOperation {
+                    operation: "get_input_buffer_count",
+                })
+            }
+
+            /// Get the physical descriptor of a HID device
+            fn get_physical_descriptor(&self, _buf: &mut [u8]) -> HidResult<usize> {
+                Err(HidError::UnsupportedOperation {
+                    operation: "get_physical_descriptor",
+                })
+            }
+
+            /// Power cycle the USB port the device is plugged into
+            fn request_reset(&self) -> HidResult<()> {
+                Err(HidError::UnsupportedOperation {
+                    operation: "request_reset",
+                })
+            }
+
+            /// Start an overlapped read and return the event which is signaled when it finishes
+            fn begin_read(&self) -> HidResult<windows_sys::Win32::Foundation::HANDLE> {
+                Err(HidError::UnsupportedOperation {
+                    operation: "begin_read",
+                })
+            }
+
+            /// Collect the result of the read started by `begin_read`
+            fn finish_read(&self, _buf: &mut [u8]) -> HidResult<usize> {
+                Err(HidError::UnsupportedOperation {
+                    operation: "finish_read",
+                })
+            }
+
+            /// Get a feature report, giving up after `timeout` milliseconds
+            fn get_feature_report_timeout(&self, _buf: &mut [u8], _timeout: i32) -> HidResult<usize> {
+                Err(HidError::UnsupportedOperation {
+                    operation: "get_feature_report_timeout",
+                })
+            }
+
+            /// Get the length of the longest Input report from the device caps
+            fn input_report_length(&self) -> HidResult<usize> {
+                Err(HidError::UnsupportedOperation {
+                    operation: "input_report_length",
+                })
+            }
+
+            /// Get the length of the longest Output report from the device caps
+            fn output_report_length(&self) -> HidResult<usize> {
+                Err(HidError::UnsupportedOperation {
+                    operation: "output_report_length",
+                })
+            }
+
+            /// Get the length of the longest Feature report from the device caps
+            fn feature_report_length(&self) -> HidResult<usize> {
+                Err(HidError::UnsupportedOperation {
+                    operation: "feature_report_length",
+                })
+            }
         }
         trait HidDeviceBackend: HidDeviceBackendBase + HidDeviceBackendWindows + Send {}
         impl<T> HidDeviceBackend for T where T: HidDeviceBackendBase + HidDeviceBackendWindows + Send {}
@@ -122,17 +246,22 @@ cfg_if! {
     }
 }
 
+#[cfg(any(feature = "std", hidapi))]
 pub type HidResult<T> = Result<T, HidError>;
+#[cfg(any(feature = "std", hidapi))]
 pub const MAX_REPORT_DESCRIPTOR_SIZE: usize = 4096;
 
+#[cfg(any(feature = "std", hidapi))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum InitState {
     NotInit,
     Init { enumerate: bool },
 }
 
+#[cfg(any(feature = "std", hidapi))]
 static INIT_STATE: Mutex<InitState> = Mutex::new(InitState::NotInit);
 
+#[cfg(any(feature = "std", hidapi))]
 fn lazy_init(do_enumerate: bool) -> HidResult<()> {
     let mut init_state = INIT_STATE.lock().unwrap();
 
@@ -171,6 +300,85 @@ fn lazy_init(do_enumerate: bool) -> HidResult<()> {
     Ok(())
 }
 
+/// A problem with a single device during enumeration.
+///
+/// The device is still part of the device list, but some of its information
+/// could not be gathered and is left at the default value.
+#[cfg(any(feature = "std", hidapi))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EnumWarning {
+    /// The bus the device is connected through could not be queried
+    ///
+    /// Devices on a bus that is simply not recognized do not cause this warning.
+    BusTypeUnknown,
+    /// The bus specific information, like the interface number, could not be read
+    BusInfoFailed,
+    /// The serial number could not be read from the device
+    SerialFetchFailed,
+}
+
+/// A handler registered with [`HidApi::set_enumeration_warning_handler()`]
+#[cfg(any(feature = "std", hidapi))]
+pub type EnumerationWarningHandler = Arc<dyn Fn(&CStr, EnumWarning) + Send + Sync>;
+
+#[cfg(any(feature = "std", hidapi))]
+static ENUMERATION_WARNING_HANDLER: RwLock<Option<EnumerationWarningHandler>> = RwLock::new(None);
+
+/// Pass a warning about the device at `path` to the registered handler, if any.
+#[cfg(any(feature = "std", hidapi))]
+#[cfg_attr(
+    not(all(feature = "windows-native", target_os = "windows")),
+    allow(dead_code)
+)]
+pub(crate) fn enumeration_warning(path: &CStr, warning: EnumWarning) {
+    // Call it without holding the lock, so that it can replace itself
+    let handler = ENUMERATION_WARNING_HANDLER.read().unwrap().clone();
+    if let Some(handler) = handler {
+        handler(path, warning);
+    }
+}
+
+/// Sort devices into the order of `HidApi::device_list()`.
+///
+/// The OS does not guarantee any order, so without sorting the same devices
+/// could be listed in a different order by every enumeration.
+#[cfg(any(feature = "std", hidapi))]
+pub(crate) fn sort_devices(devices: &mut [DeviceInfo]) {
+    devices.sort_by(device_order);
+}
+
+/// The order of `HidApi::device_list()`: by path, then interface number, usage page and usage
+#[cfg(any(feature = "std", hidapi))]
+pub(crate) fn device_order(a: &DeviceInfo, b: &DeviceInfo) -> std::cmp::Ordering {
+    (&a.path, a.interface_number, a.usage_page, a.usage).cmp(&(
+        &b.path,
+        b.interface_number,
+        b.usage_page,
+        b.usage,
+    ))
+}
+
+/// Whether opening a device might succeed later on, after failing with `err`.
+#[cfg(any(feature = "std", hidapi))]
+fn is_retryable(err: &HidError) -> bool {
+    use std::io::ErrorKind;
+
+    match err {
+        // The C library reports all failures to open a device as a plain message
+        HidError::DeviceNotFound
+        | HidError::Disconnected
+        | HidError::HidApiError { .. }
+        | HidError::HidApiErrorEmpty => true,
+        // The device node may not exist yet, or the device is still being set up
+        HidError::IoError { .. } | HidError::Backend { .. } => matches!(
+            err.io_error_kind(),
+            ErrorKind::NotFound | ErrorKind::NotConnected
+        ),
+        _ => false,
+    }
+}
+
 /// `hidapi` context.
 ///
 /// The `hidapi` C library is lazily initialized when creating the first instance,
@@ -178,10 +386,14 @@ fn lazy_init(do_enumerate: bool) -> HidResult<()> {
 /// instances.
 ///
 /// Each instance has its own device list cache.
+#[cfg(any(feature = "std", hidapi))]
 pub struct HidApi {
     device_list: Vec<DeviceInfo>,
+    /// When the device list was last filled with all devices
+    last_refresh: Option<Instant>,
 }
 
+#[cfg(any(feature = "std", hidapi))]
 impl HidApi {
     /// Create a new hidapi context.
     ///
@@ -192,13 +404,7 @@ impl HidApi {
     /// Panics if hidapi is already initialized in "without enumerate" mode
     /// (i.e. if `new_without_enumerate()` has been called before).
     pub fn new() -> HidResult<Self> {
-        lazy_init(true)?;
-
-        let mut api = HidApi {
-            device_list: Vec::with_capacity(8),
-        };
-        api.add_devices(0, 0)?;
-        Ok(api)
+        Self::builder().build()
     }
 
     /// Create a new hidapi context, in "do not enumerate" mode.
@@ -214,46 +420,263 @@ impl HidApi {
 
         Ok(HidApi {
             device_list: Vec::new(),
+            last_refresh: None,
         })
     }
 
+    /// Create a new hidapi context using the given backend.
+    ///
+    /// The backend is selected at compile time through the feature flags, so
+    /// this fails with [`HidError::UnsupportedBackend`] if `backend` is not the
+    /// one this build uses. Otherwise it behaves just like [`HidApi::new()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if hidapi is already initialized in "without enumerate" mode
+    /// (i.e. if `new_without_enumerate()` has been called before).
+    pub fn new_with_backend(backend: ApiBackend) -> HidResult<Self> {
+        if backend != ApiBackend::CURRENT {
+            return Err(HidError::UnsupportedBackend);
+        }
+        Self::new()
+    }
+
+    /// Create a [`HidApiBuilder`] to configure how the context is constructed.
+    pub fn builder() -> HidApiBuilder {
+        HidApiBuilder::new()
+    }
+
     /// Refresh devices list and information about them (to access them use
     /// `device_list()` method)
     /// Identical to `reset_devices()` followed by `add_devices(0, 0)`.
     pub fn refresh_devices(&mut self) -> HidResult<()> {
         self.reset_devices()?;
         self.add_devices(0, 0)?;
+        self.last_refresh = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Returns the device list, refreshing it first if it is older than `max_age`.
+    ///
+    /// The list counts as fresh from the last successful `refresh_devices()`
+    /// (or `refresh_devices_timeout()`), including the one done by `new()`.
+    /// Lists which were only built with `add_devices()` are always refreshed.
+    pub fn devices_cached(
+        &mut self,
+        max_age: Duration,
+    ) -> HidResult<impl Iterator<Item = &DeviceInfo>> {
+        if self.last_refresh.is_none_or(|at| at.elapsed() > max_age) {
+            self.refresh_devices()?;
+        }
+        Ok(self.device_list())
+    }
+
+    /// Refresh devices list like `refresh_devices()`, but give up after `timeout`.
+    ///
+    /// The enumeration runs on a worker thread, see [`HidApi::enumerate_timeout()`].
+    /// The device list is only replaced once the enumeration finished. If it
+    /// fails or does not finish in time, the previous list is kept and the
+    /// error, like [`HidError::Timeout`], is returned. Use
+    /// [`HidApi::enumerate_timeout()`] to get the devices found so far instead.
+    pub fn refresh_devices_timeout(&mut self, timeout: Duration) -> HidResult<()> {
+        let mut devices = Vec::new();
+        self.enumerate_timeout(0, 0, timeout, &mut devices)?;
+        self.device_list = devices;
+        self.last_refresh = Some(Instant::now());
         Ok(())
     }
 
+    /// Enumerate the devices that match the given VID and PID filters into
+    /// `out` like [`HidApi::enumerate_into()`], but give up after `timeout`.
+    ///
+    /// The enumeration runs on a worker thread. If it does not finish in time,
+    /// `out` holds the devices found so far and [`HidError::Timeout`] is
+    /// returned. The worker thread can not be interrupted, so it finishes the
+    /// enumeration in the background. The backends which use the C library
+    /// serialize enumerating and opening devices, so opening a device waits for
+    /// the worker until then. If enumerating fails, `out` holds the devices
+    /// found before the failure. The devices in `out` are always sorted like
+    /// in [`HidApi::device_list()`].
+    pub fn enumerate_timeout(
+        &self,
+        vid: u16,
+        pid: u16,
+        timeout: Duration,
+        out: &mut Vec<DeviceInfo>,
+    ) -> HidResult<()> {
+        out.clear();
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let res = HidApiBackend::enumerate_with(vid, pid, |info| {
+                let _ = sender.send(Ok(Some(info)));
+            });
+            let _ = sender.send(res.map(|()| None));
+        });
+
+        let deadline = Instant::now() + timeout;
+        let res = loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(remaining) {
+                Ok(Ok(Some(info))) => out.push(info),
+                Ok(Ok(None)) => break Ok(()),
+                Ok(Err(e)) => break Err(e),
+                Err(RecvTimeoutError::Timeout) => break Err(HidError::Timeout),
+                Err(RecvTimeoutError::Disconnected) => {
+                    break Err(HidError::HidApiError {
+                        message: "enumeration thread stopped unexpectedly".into(),
+                    })
+                }
+            }
+        };
+        sort_devices(out);
+        res
+    }
+
     /// Reset devices list. Intended to be used with the `add_devices` method.
     pub fn reset_devices(&mut self) -> HidResult<()> {
         self.device_list.clear();
+        self.last_refresh = None;
         Ok(())
     }
 
     /// Indexes devices that match the given VID and PID filters.
     /// 0 indicates no filter.
     pub fn add_devices(&mut self, vid: u16, pid: u16) -> HidResult<()> {
-        self.device_list
-            .append(&mut HidApiBackend::get_hid_device_info_vector(vid, pid)?);
+        let res = HidApiBackend::enumerate_with(vid, pid, |info| self.device_list.push(info));
+        sort_devices(&mut self.device_list);
+        res
+    }
+
+    /// Indexes the devices on the given bus.
+    ///
+    /// The `windows-native` backend determines the bus of each device before
+    /// opening it, which skips most of the work for devices on other buses.
+    /// The other backends enumerate all devices and filter them afterwards.
+    ///
+    /// The bus type has to match exactly, so [`BusType::Bluetooth`] does not
+    /// include the devices which are reported as [`BusType::BluetoothLE`].
+    pub fn add_devices_for_bus(&mut self, bus: BusType) -> HidResult<()> {
+        let devices = HidApiBackend::get_hid_device_info_vector_for_bus(bus)?;
+        self.device_list.extend(devices);
+        sort_devices(&mut self.device_list);
         Ok(())
     }
 
+    /// Enumerate the devices that match the given VID and PID filters into `out`.
+    /// 0 indicates no filter.
+    ///
+    /// The previous contents of `out` are cleared, but its capacity is reused.
+    /// This avoids reallocating the list when enumerating repeatedly. The
+    /// internal device list is not touched. The devices are sorted like in
+    /// [`HidApi::device_list()`].
+    pub fn enumerate_into(&self, vid: u16, pid: u16, out: &mut Vec<DeviceInfo>) -> HidResult<()> {
+        out.clear();
+        let res = HidApiBackend::enumerate_with(vid, pid, |info| out.push(info));
+        sort_devices(out);
+        res
+    }
+
     /// Returns iterator containing information about attached HID devices
     /// that have been indexed, either by `refresh_devices` or `add_devices`.
+    ///
+    /// The devices are sorted by path, then interface number, usage page and
+    /// usage. So as long as the same devices are attached, every enumeration
+    /// lists them in the same order, and the index of a device stays the same.
     pub fn device_list(&self) -> impl Iterator<Item = &DeviceInfo> {
         self.device_list.iter()
     }
 
     /// Open a HID device using a Vendor ID (VID) and Product ID (PID).
     ///
-    /// When multiple devices with the same vid and pid are available, then the
-    /// first one found in the internal device list will be used. There are however
-    /// no guarantees, which device this will be.
+    /// When multiple interfaces with the same vid and pid are available, the
+    /// first one in the order of [`HidApi::device_list()`] is opened: the one
+    /// with the lowest path, then the lowest interface number, usage page and
+    /// usage. The other `HidApi` methods which pick one of several devices,
+    /// like [`HidApi::open_nth()`] and [`HidApi::wait_for_device()`], use the
+    /// same order.
     pub fn open(&self, vid: u16, pid: u16) -> HidResult<HidDevice> {
-        let dev = HidApiBackend::open(vid, pid)?;
-        Ok(HidDevice::from_backend(Box::new(dev)))
+        let (dev, _) = self.open_nth(vid, pid, 0)?;
+        Ok(dev)
+    }
+
+    /// Open the `n`-th interface with the given Vendor ID (VID) and Product ID (PID).
+    ///
+    /// The currently attached interfaces are enumerated and sorted like in
+    /// [`HidApi::device_list()`]. This order does not depend on the order in
+    /// which the OS lists the interfaces, so `n` refers to the same interface
+    /// every time, as long as the same devices are attached.
+    ///
+    /// The device is returned together with the [`DeviceInfo`] that was used
+    /// to open it. Returns [`HidError::DeviceNotFound`] if there are not more
+    /// than `n` matching interfaces.
+    pub fn open_nth(&self, vid: u16, pid: u16, n: usize) -> HidResult<(HidDevice, DeviceInfo)> {
+        let mut infos = HidApiBackend::get_hid_device_info_vector(vid, pid)?;
+        infos.retain(|info| info.vendor_id == vid && info.product_id == pid);
+        sort_devices(&mut infos);
+        let info = infos.into_iter().nth(n).ok_or(HidError::DeviceNotFound)?;
+        let dev = self.open_path(info.path())?;
+        Ok((dev, info))
+    }
+
+    /// Open a HID device using a Vendor ID (VID) and Product ID (PID), trying
+    /// up to `attempts` times.
+    ///
+    /// This is meant for devices which come and go, like Bluetooth devices that
+    /// reconnect. After a failed attempt the calling thread sleeps for `backoff`
+    /// before trying again. Only errors which can go away on their own are
+    /// retried, like [`HidError::DeviceNotFound`] or an error of the OS while
+    /// the device is still being set up. Other errors are returned right away.
+    /// At least one attempt is made, even if `attempts` is 0.
+    ///
+    /// Returns the error of the last attempt if all of them failed.
+    pub fn open_with_retry(
+        &self,
+        vid: u16,
+        pid: u16,
+        attempts: u32,
+        backoff: Duration,
+    ) -> HidResult<HidDevice> {
+        let mut attempt = 1;
+        loop {
+            match self.open(vid, pid) {
+                Err(e) if attempt < attempts && is_retryable(&e) => {
+                    attempt += 1;
+                    thread::sleep(backoff);
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Wait until a HID device with the given Vendor ID (VID) and Product ID
+    /// (PID) is connected and return its info.
+    ///
+    /// This is meant for devices which were just powered on or plugged in and
+    /// still have to enumerate. The devices are enumerated every 100 ms until
+    /// a matching one shows up, blocking the calling thread. If several
+    /// interfaces match, the first one in the order of
+    /// [`HidApi::device_list()`] is returned. The device list of this
+    /// `HidApi` is not changed.
+    ///
+    /// Returns [`HidError::Timeout`] if no device showed up within `timeout`.
+    pub fn wait_for_device(&self, vid: u16, pid: u16, timeout: Duration) -> HidResult<DeviceInfo> {
+        const POLL_INTERVAL: Duration = Duration::from_millis(100);
+        let deadline = Instant::now() + timeout;
+        loop {
+            let mut infos = HidApiBackend::get_hid_device_info_vector(vid, pid)?;
+            infos.retain(|info| info.vendor_id == vid && info.product_id == pid);
+            sort_devices(&mut infos);
+            if let Some(info) = infos.into_iter().next() {
+                return Ok(info);
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(HidError::Timeout);
+            }
+            thread::sleep(remaining.min(POLL_INTERVAL));
+        }
     }
 
     /// Open a HID device using a Vendor ID (VID), Product ID (PID) and
@@ -263,6 +686,63 @@ impl HidApi {
         Ok(HidDevice::from_backend(Box::new(dev)))
     }
 
+    /// Open the interface of a HID device with the given Vendor ID (VID),
+    /// Product ID (PID), usage page and usage.
+    ///
+    /// This is useful for devices with multiple interfaces, where opening by
+    /// vid and pid alone could end up with any of them. Returns
+    /// [`HidError::DeviceNotFound`] if no interface matches.
+    ///
+    /// Usage page and usage are not available on linux libusb backends.
+    #[cfg(not(all(libusb, target_os = "linux")))]
+    pub fn open_usage(
+        &self,
+        vid: u16,
+        pid: u16,
+        usage_page: u16,
+        usage: u16,
+    ) -> HidResult<HidDevice> {
+        let (dev, _) = self
+            .open_first(|info| info.matches(Some(vid), Some(pid), Some(usage_page), Some(usage)))?;
+        Ok(dev)
+    }
+
+    /// Open the first HID device whose [`DeviceInfo::friendly_name()`] contains
+    /// `substring`, ignoring case.
+    ///
+    /// Returns [`HidError::DeviceNotFound`] if no device matches. Friendly names
+    /// are only available with the `windows-native` backend, so this never finds
+    /// a device with the other backends.
+    pub fn open_by_name(&self, substring: &str) -> HidResult<HidDevice> {
+        let substring = substring.to_lowercase();
+        let (dev, _) = self.open_first(|info| {
+            info.friendly_name()
+                .is_some_and(|name| name.to_lowercase().contains(&substring))
+        })?;
+        Ok(dev)
+    }
+
+    /// Open the first HID device for which `filter` returns `true`.
+    ///
+    /// The currently attached devices are enumerated, independent of the
+    /// internal device list, and the first match in the order of
+    /// [`HidApi::device_list()`] is opened by its path.
+    /// The device is returned together with the [`DeviceInfo`] that was used
+    /// to open it. Returns [`HidError::DeviceNotFound`] if no device matches.
+    pub fn open_first(
+        &self,
+        filter: impl Fn(&DeviceInfo) -> bool,
+    ) -> HidResult<(HidDevice, DeviceInfo)> {
+        let mut infos = HidApiBackend::get_hid_device_info_vector(0, 0)?;
+        sort_devices(&mut infos);
+        let info = infos
+            .into_iter()
+            .find(|info| filter(info))
+            .ok_or(HidError::DeviceNotFound)?;
+        let dev = self.open_path(info.path())?;
+        Ok((dev, info))
+    }
+
     /// The path name be determined by inspecting the device list available with [HidApi::devices()](struct.HidApi.html#method.devices)
     ///
     /// Alternatively a platform-specific path name can be used (eg: /dev/hidraw0 on Linux).
@@ -271,20 +751,71 @@ impl HidApi {
         Ok(HidDevice::from_backend(Box::new(dev)))
     }
 
+    /// Open a HID device by its path, requesting only the given `access`.
+    ///
+    /// This leaves the access which is not needed to other processes, e.g.
+    /// a device that only receives Output reports can be opened with
+    /// [`Access::Write`]. Unlike [`HidApi::open_path()`], there is no fallback
+    /// to another access mode: if the requested access is denied, this fails
+    /// with an error which converts into a [`std::io::Error`] of kind
+    /// [`PermissionDenied`](std::io::ErrorKind::PermissionDenied). That is an
+    /// [`HidError::IoError`], or an [`HidError::Backend`] on Windows.
+    ///
+    /// The C library always opens devices for reading and writing, so with the
+    /// `hidapi` backends only [`Access::ReadWrite`] is supported.
+    pub fn open_path_with_access(
+        &self,
+        device_path: &CStr,
+        access: Access,
+    ) -> HidResult<HidDevice> {
+        let dev = HidApiBackend::open_path_with_access(device_path, access)?;
+        Ok(HidDevice::from_backend(Box::new(dev)))
+    }
+
     /// Open a HID device using libusb_wrap_sys_device.
     #[cfg(libusb)]
     pub fn wrap_sys_device(&self, sys_dev: isize, interface_num: i32) -> HidResult<HidDevice> {
-        let device = unsafe { ffi::hid_libusb_wrap_sys_device(sys_dev, interface_num) };
+        let dev = HidApiBackend::open_with(|| unsafe {
+            ffi::hid_libusb_wrap_sys_device(sys_dev, interface_num)
+        })?;
+        Ok(HidDevice::from_backend(Box::new(dev)))
+    }
 
-        if device.is_null() {
-            match HidApiBackend::check_error() {
-                Ok(err) => Err(err),
-                Err(e) => Err(e),
-            }
-        } else {
-            let dev = hidapi::HidDevice::from_raw(device);
-            Ok(HidDevice::from_backend(Box::new(dev)))
-        }
+    /// Register a handler which is called for devices whose information could
+    /// only be gathered partially during enumeration.
+    ///
+    /// The handler is global for all `HidApi` instances and replaces any
+    /// previously registered one. It receives the path of the device and the
+    /// reason. Enumeration itself is not affected, the device is still listed.
+    ///
+    /// Currently only the `windows-native` backend reports warnings. It also
+    /// gathers the information of a device when opening it, so the handler
+    /// can be called from the `open` methods as well.
+    ///
+    /// The handler may be called from several threads at the same time, as the
+    /// `windows-native` backend gathers the information on a few threads during
+    /// enumeration. It may register a new handler itself.
+    pub fn set_enumeration_warning_handler(handler: EnumerationWarningHandler) {
+        *ENUMERATION_WARNING_HANDLER.write().unwrap() = Some(handler);
+    }
+
+    /// Get the version of the `hidapi` C library as `(major, minor, patch)`.
+    ///
+    /// This is the version of the library which is actually linked, which can
+    /// differ from the bundled one if a shared library is used. Only available
+    /// with the backends which use the C library.
+    #[cfg(hidapi)]
+    pub fn library_version() -> (u16, u16, u16) {
+        HidApiBackend::library_version()
+    }
+
+    /// Get the version of the `hidapi` C library as a string, e.g. `"0.14.0"`.
+    ///
+    /// Unlike [`HidApi::library_version()`], this can include a suffix of the
+    /// library build. Only available with the backends which use the C library.
+    #[cfg(hidapi)]
+    pub fn library_version_str() -> String {
+        HidApiBackend::library_version_str()
     }
 
     /// Get the last non-device specific error, which happened in the underlying hidapi C library.
@@ -295,6 +826,11 @@ impl HidApi {
     /// When `Err()` is returned, then acquiring the error string from the hidapi C
     /// library failed. The contained [HidError](enum.HidError.html) is the cause, why no error could
     /// be fetched.
+    ///
+    /// The non-device specific error is shared by all threads, so it can be
+    /// overwritten by a failure on another thread before it is read. The
+    /// `open` methods capture it right away and return it, and the errors of
+    /// [`HidDevice::check_error`] are kept per device, so both are thread-safe.
     #[cfg(hidapi)]
     #[deprecated(since = "2.2.3", note = "use the return values from the other methods")]
     pub fn check_error(&self) -> HidResult<HidError> {
@@ -302,33 +838,84 @@ impl HidApi {
     }
 }
 
-#[allow(dead_code)]
-#[derive(Clone, PartialEq)]
-enum WcharString {
-    String(String),
-    #[cfg_attr(all(feature = "linux-native", target_os = "linux"), allow(dead_code))]
-    Raw(Vec<wchar_t>),
-    None,
+/// The kind of backend which is used to talk to the devices.
+#[cfg(any(feature = "std", hidapi))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ApiBackend {
+    /// The platform's own HID API, either through the `hidapi` C library or
+    /// through one of the native rust backends
+    Native,
+    /// The libusb backend of the `hidapi` C library
+    Libusb,
 }
 
-impl From<WcharString> for Option<String> {
-    fn from(val: WcharString) -> Self {
-        match val {
-            WcharString::String(s) => Some(s),
-            _ => None,
+#[cfg(any(feature = "std", hidapi))]
+impl ApiBackend {
+    #[cfg(libusb)]
+    const CURRENT: ApiBackend = ApiBackend::Libusb;
+    #[cfg(not(libusb))]
+    const CURRENT: ApiBackend = ApiBackend::Native;
+}
+
+/// Builder for a [`HidApi`] context.
+///
+/// ```rust,no_run
+/// use hidapi::HidApi;
+///
+/// // Skip the initial device scan, the device list stays empty
+/// // until `refresh_devices()` or `add_devices()` is called.
+/// let api = HidApi::builder().enumerate(false).build().unwrap();
+/// ```
+#[cfg(any(feature = "std", hidapi))]
+#[derive(Debug, Clone)]
+pub struct HidApiBuilder {
+    enumerate: bool,
+}
+
+#[cfg(any(feature = "std", hidapi))]
+impl HidApiBuilder {
+    /// Create a builder with the default settings, which match [`HidApi::new()`].
+    pub fn new() -> Self {
+        Self { enumerate: true }
+    }
+
+    /// Whether the device list should be populated when building the context.
+    ///
+    /// Defaults to `true`.
+    ///
+    /// Unlike [`HidApi::new_without_enumerate()`], this only skips the initial
+    /// scan. The underlying library is still initialized normally, so the
+    /// device list can be filled later on with [`HidApi::refresh_devices()`].
+    pub fn enumerate(mut self, enumerate: bool) -> Self {
+        self.enumerate = enumerate;
+        self
+    }
+
+    /// Create the hidapi context.
+    ///
+    /// # Panics
+    ///
+    /// Panics if hidapi is already initialized in "without enumerate" mode
+    /// (i.e. if `new_without_enumerate()` has been called before).
+    pub fn build(self) -> HidResult<HidApi> {
+        lazy_init(true)?;
+
+        let mut api = HidApi {
+            device_list: Vec::with_capacity(8),
+            last_refresh: None,
+        };
+        if self.enumerate {
+            api.refresh_devices()?;
         }
+        Ok(api)
     }
 }
 
-/// The underlying HID bus type.
-#[repr(C)]
-#[derive(Copy, Clone, Debug)]
-pub enum BusType {
-    Unknown = 0x00,
-    Usb = 0x01,
-    Bluetooth = 0x02,
-    I2c = 0x03,
-    Spi = 0x04,
+#[cfg(any(feature = "std", hidapi))]
+impl Default for HidApiBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Device information. Use accessors to extract information about Hid devices.
@@ -336,7 +923,16 @@ pub enum BusType {
 /// Note: Methods like `serial_number()` may return None, if the conversion to a
 /// String failed internally. You can however access the raw hid representation of the
 /// string by calling `serial_number_raw()`
+///
+/// Two `DeviceInfo`s are equal if they refer to the same device interface:
+/// only the path, vendor and product id, interface number, usage page and
+/// usage are compared and hashed. The strings, release number, bus type and
+/// report lengths are not part of the identity. On Windows, the paths are
+/// compared the way [`DeviceInfo::stable_id()`] normalizes them, as Windows
+/// does not keep the casing of device interface paths consistent.
+#[cfg(any(feature = "std", hidapi))]
 #[derive(Clone)]
+#[non_exhaustive]
 pub struct DeviceInfo {
     path: CString,
     vendor_id: u16,
@@ -349,15 +945,35 @@ pub struct DeviceInfo {
     usage_page: u16,
     #[allow(dead_code)]
     usage: u16,
+    /// Whether `usage_page` and `usage` were actually read from the device
+    usage_known: bool,
     interface_number: i32,
     bus_type: BusType,
+    input_report_length: Option<u16>,
+    output_report_length: Option<u16>,
+    feature_report_length: Option<u16>,
+    usb_bcd_device: Option<u16>,
+    present: bool,
+    friendly_name: Option<String>,
+    #[cfg(target_os = "windows")]
+    container_id: Option<GUID>,
+    #[cfg(target_os = "windows")]
+    parent_instance_id: Option<String>,
+    #[cfg(target_os = "windows")]
+    instance_id: Option<String>,
 }
 
+#[cfg(any(feature = "std", hidapi))]
 impl DeviceInfo {
     pub fn path(&self) -> &CStr {
         &self.path
     }
 
+    /// The device path as string, invalid UTF-8 is replaced with `U+FFFD`.
+    pub fn path_str(&self) -> Cow<'_, str> {
+        self.path.to_string_lossy()
+    }
+
     pub fn vendor_id(&self) -> u16 {
         self.vendor_id
     }
@@ -375,16 +991,35 @@ impl DeviceInfo {
     }
 
     pub fn serial_number_raw(&self) -> Option<&[wchar_t]> {
-        match self.serial_number {
-            WcharString::Raw(ref s) => Some(s),
-            _ => None,
-        }
+        self.serial_number.as_raw()
+    }
+
+    /// Like `serial_number()`, but invalid characters are replaced with `U+FFFD`
+    /// instead of giving up. Empty if the string is not available.
+    pub fn serial_number_lossy(&self) -> String {
+        self.serial_number.repair()
     }
 
     pub fn release_number(&self) -> u16 {
         self.release_number
     }
 
+    /// Decode the BCD encoded release number into `(major, minor)`.
+    ///
+    /// For example `0x0210` is decoded as `(2, 10)`. Nibbles that are not valid
+    /// BCD digits are not rejected, they are just used as they are.
+    pub fn release_version(&self) -> (u8, u8) {
+        let bcd = |byte: u8| (byte >> 4) * 10 + (byte & 0x0F);
+        let [major, minor] = self.release_number.to_be_bytes();
+        (bcd(major), bcd(minor))
+    }
+
+    /// The release number formatted as version string, e.g. `"2.10"`.
+    pub fn release_version_string(&self) -> String {
+        let (major, minor) = self.release_version();
+        format!("{}.{:02}", major, minor)
+    }
+
     /// Try to call `manufacturer_string_raw()`, if None is returned.
     pub fn manufacturer_string(&self) -> Option<&str> {
         match self.manufacturer_string {
@@ -394,10 +1029,13 @@ impl DeviceInfo {
     }
 
     pub fn manufacturer_string_raw(&self) -> Option<&[wchar_t]> {
-        match self.manufacturer_string {
-            WcharString::Raw(ref s) => Some(s),
-            _ => None,
-        }
+        self.manufacturer_string.as_raw()
+    }
+
+    /// Like `manufacturer_string()`, but invalid characters are replaced with `U+FFFD`
+    /// instead of giving up. Empty if the string is not available.
+    pub fn manufacturer_string_lossy(&self) -> String {
+        self.manufacturer_string.repair()
     }
 
     /// Try to call `product_string_raw()`, if None is returned.
@@ -409,10 +1047,13 @@ impl DeviceInfo {
     }
 
     pub fn product_string_raw(&self) -> Option<&[wchar_t]> {
-        match self.product_string {
-            WcharString::Raw(ref s) => Some(s),
-            _ => None,
-        }
+        self.product_string.as_raw()
+    }
+
+    /// Like `product_string()`, but invalid characters are replaced with `U+FFFD`
+    /// instead of giving up. Empty if the string is not available.
+    pub fn product_string_lossy(&self) -> String {
+        self.product_string.repair()
     }
 
     /// Usage page is not available on linux libusb backends
@@ -427,14 +1068,115 @@ impl DeviceInfo {
         self.usage
     }
 
+    /// The usage page, or `None` if the backend could not read it.
+    ///
+    /// Unlike [`DeviceInfo::usage_page()`], this tells a failed query apart
+    /// from a device which really uses the usage page 0. Always `None` on
+    /// linux libusb backends.
+    pub fn try_usage_page(&self) -> Option<u16> {
+        self.usage_known.then_some(self.usage_page)
+    }
+
+    /// The usage, or `None` if the backend could not read it.
+    ///
+    /// See [`DeviceInfo::try_usage_page()`].
+    pub fn try_usage(&self) -> Option<u16> {
+        self.usage_known.then_some(self.usage)
+    }
+
+    /// Same as [`DeviceInfo::usage_page()`], but as [`UsagePage`]
+    #[cfg(not(all(libusb, target_os = "linux")))]
+    pub fn typed_usage_page(&self) -> UsagePage {
+        UsagePage(self.usage_page)
+    }
+
+    /// Same as [`DeviceInfo::usage()`], but as [`Usage`]
+    #[cfg(not(all(libusb, target_os = "linux")))]
+    pub fn typed_usage(&self) -> Usage {
+        Usage(self.usage)
+    }
+
     pub fn interface_number(&self) -> i32 {
         self.interface_number
     }
 
+    /// The interface number, or `None` if it is not known.
+    ///
+    /// Unlike [`DeviceInfo::interface_number()`], this does not use `-1` for
+    /// unknown interfaces.
+    pub fn interface(&self) -> Option<u8> {
+        u8::try_from(self.interface_number).ok()
+    }
+
     pub fn bus_type(&self) -> BusType {
         self.bus_type
     }
 
+    /// Maximum length of an Input report in bytes, including the report ID.
+    ///
+    /// Only available on the `windows-native` backend.
+    pub fn input_report_length(&self) -> Option<u16> {
+        self.input_report_length
+    }
+
+    /// Maximum length of an Output report in bytes, including the report ID.
+    ///
+    /// Only available on the `windows-native` backend.
+    pub fn output_report_length(&self) -> Option<u16> {
+        self.output_report_length
+    }
+
+    /// Maximum length of a Feature report in bytes, including the report ID.
+    ///
+    /// Only available on the `windows-native` backend.
+    pub fn feature_report_length(&self) -> Option<u16> {
+        self.feature_report_length
+    }
+
+    /// The `bcdDevice` release number of the USB device descriptor.
+    ///
+    /// [`DeviceInfo::release_number()`] usually holds the same value, but some
+    /// backends report the version of the HID attributes there, which can differ.
+    /// Only available for USB devices with the `linux-native` and `windows-native` backends.
+    pub fn usb_bcd_device(&self) -> Option<u16> {
+        self.usb_bcd_device
+    }
+
+    /// Whether the device is currently connected.
+    ///
+    /// This is only `false` for the installed but disconnected devices which
+    /// are listed by `HidApi::device_list_all()` on Windows.
+    pub fn is_present(&self) -> bool {
+        self.present
+    }
+
+    /// The name of the device which the OS shows to users, like the one in
+    /// the Windows Device Manager.
+    ///
+    /// Only available with the `windows-native` backend.
+    pub fn friendly_name(&self) -> Option<&str> {
+        self.friendly_name.as_deref()
+    }
+
+    /// Check whether the device matches the given filters.
+    ///
+    /// A filter that is `None` matches any value.
+    ///
+    /// Note, that usage page and usage are always reported as 0 on linux libusb
+    /// backends, so filtering on them will not match any device there.
+    pub fn matches(
+        &self,
+        vid: Option<u16>,
+        pid: Option<u16>,
+        usage_page: Option<u16>,
+        usage: Option<u16>,
+    ) -> bool {
+        vid.is_none_or(|vid| vid == self.vendor_id)
+            && pid.is_none_or(|pid| pid == self.product_id)
+            && usage_page.is_none_or(|usage_page| usage_page == self.usage_page)
+            && usage.is_none_or(|usage| usage == self.usage)
+    }
+
     /// Use the information contained in `DeviceInfo` to open
     /// and return a handle to a [HidDevice](struct.HidDevice.html).
     ///
@@ -457,6 +1199,67 @@ impl DeviceInfo {
     }
 }
 
+/// Open exactly the enumerated interface, by its path.
+///
+/// A `DeviceInfo` can only be obtained through a [`HidApi`], so the underlying
+/// library is already initialized. Unlike [`DeviceInfo::open_device()`], this
+/// does not fall back to the serial number and fails with
+/// [`HidError::OpenHidDeviceWithDeviceInfoError`] if the path is empty.
+#[cfg(any(feature = "std", hidapi))]
+impl TryFrom<&DeviceInfo> for HidDevice {
+    type Error = HidError;
+
+    fn try_from(info: &DeviceInfo) -> HidResult<Self> {
+        if info.path.as_bytes().is_empty() {
+            return Err(HidError::OpenHidDeviceWithDeviceInfoError {
+                device_info: Box::new(info.clone()),
+            });
+        }
+        let dev = HidApiBackend::open_path(&info.path)?;
+        Ok(HidDevice::from_backend(Box::new(dev)))
+    }
+}
+
+/// The device path in the form in which it is compared and hashed
+#[cfg(all(any(feature = "std", hidapi), target_os = "windows"))]
+fn path_key(path: &CStr) -> Cow<'_, [u8]> {
+    Cow::Owned(windows::normalize_path(path.to_bytes()))
+}
+
+/// The device path in the form in which it is compared and hashed
+#[cfg(all(any(feature = "std", hidapi), not(target_os = "windows")))]
+fn path_key(path: &CStr) -> Cow<'_, [u8]> {
+    Cow::Borrowed(path.to_bytes())
+}
+
+#[cfg(any(feature = "std", hidapi))]
+impl PartialEq for DeviceInfo {
+    fn eq(&self, other: &Self) -> bool {
+        path_key(&self.path) == path_key(&other.path)
+            && self.vendor_id == other.vendor_id
+            && self.product_id == other.product_id
+            && self.interface_number == other.interface_number
+            && self.usage_page == other.usage_page
+            && self.usage == other.usage
+    }
+}
+
+#[cfg(any(feature = "std", hidapi))]
+impl Eq for DeviceInfo {}
+
+#[cfg(any(feature = "std", hidapi))]
+impl Hash for DeviceInfo {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        path_key(&self.path).hash(state);
+        self.vendor_id.hash(state);
+        self.product_id.hash(state);
+        self.interface_number.hash(state);
+        self.usage_page.hash(state);
+        self.usage.hash(state);
+    }
+}
+
+#[cfg(any(feature = "std", hidapi))]
 impl fmt::Debug for DeviceInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("HidDeviceInfo")
@@ -466,7 +1269,69 @@ impl fmt::Debug for DeviceInfo {
     }
 }
 
+/// Remembers the path of a device, so that it can be reopened cheaply.
+///
+/// This is meant for reconnection loops: after the device got disconnected,
+/// [`OpenHandle::reopen()`] opens the same path again without rebuilding it.
+#[cfg(any(feature = "std", hidapi))]
+#[derive(Debug, Clone)]
+pub struct OpenHandle {
+    path: CString,
+}
+
+#[cfg(any(feature = "std", hidapi))]
+impl OpenHandle {
+    /// Create a handle for the device described by `info`.
+    ///
+    /// The `HidApi` is only needed to make sure the backend is initialized.
+    /// Fails with [`HidError::OpenHidDeviceWithDeviceInfoError`] if `info` has no path.
+    pub fn new(_api: &HidApi, info: &DeviceInfo) -> HidResult<Self> {
+        if info.path.as_bytes().is_empty() {
+            return Err(HidError::OpenHidDeviceWithDeviceInfoError {
+                device_info: Box::new(info.clone()),
+            });
+        }
+        Ok(Self {
+            path: info.path.clone(),
+        })
+    }
+
+    /// The path which is opened by [`OpenHandle::reopen()`]
+    pub fn path(&self) -> &CStr {
+        &self.path
+    }
+
+    /// Open the device at the remembered path.
+    pub fn reopen(&self) -> HidResult<HidDevice> {
+        let dev = HidApiBackend::open_path(&self.path)?;
+        Ok(HidDevice::from_backend(Box::new(dev)))
+    }
+}
+
+/// Convert an optional timeout into milliseconds, where -1 means to wait forever
+#[cfg(any(feature = "std", hidapi))]
+fn timeout_millis(timeout: Option<Duration>) -> i32 {
+    match timeout {
+        Some(timeout) => timeout.as_millis().min(i32::MAX as u128) as i32,
+        None => -1,
+    }
+}
+
+/// Make sure the length reported by the backend fits into `buf`, so that callers
+/// can trust it for slicing even if the device or driver misbehaves
+#[cfg(any(feature = "std", hidapi))]
+fn check_report_len(len: usize, buf: &[u8]) -> HidResult<usize> {
+    if len > buf.len() {
+        return Err(HidError::OversizedReport {
+            reported: len,
+            buffer: buf.len(),
+        });
+    }
+    Ok(len)
+}
+
 /// Trait which the different backends must implement
+#[cfg(any(feature = "std", hidapi))]
 trait HidDeviceBackendBase {
     #[cfg(hidapi)]
     fn check_error(&self) -> HidResult<HidError>;
@@ -481,31 +1346,197 @@ trait HidDeviceBackendBase {
     fn get_product_string(&self) -> HidResult<Option<String>>;
     fn get_serial_number_string(&self) -> HidResult<Option<String>>;
     fn get_report_descriptor(&self, buf: &mut [u8]) -> HidResult<usize>;
+    fn close(self: Box<Self>) -> HidResult<()>;
+
+    fn get_report_descriptor_size(&self) -> HidResult<usize> {
+        // Without a way to query the size, fetch the whole descriptor
+        let mut buf = vec![0u8; MAX_REPORT_DESCRIPTOR_SIZE];
+        self.get_report_descriptor(&mut buf)
+    }
 
     fn get_indexed_string(&self, _index: i32) -> HidResult<Option<String>> {
-        Err(HidError::HidApiError {
-            message: "get_indexed_string: not supported".to_string(),
+        Err(HidError::UnsupportedOperation {
+            operation: "get_indexed_string",
+        })
+    }
+
+    fn get_indexed_string_lang(&self, _index: i32, _lang_id: u16) -> HidResult<Option<String>> {
+        Err(HidError::UnsupportedOperation {
+            operation: "get_indexed_string_lang",
+        })
+    }
+
+    fn get_supported_languages(&self) -> HidResult<Vec<u16>> {
+        Err(HidError::UnsupportedOperation {
+            operation: "get_supported_languages",
+        })
+    }
+
+    fn send_output_report(&self, _data: &[u8]) -> HidResult<usize> {
+        Err(HidError::UnsupportedOperation {
+            operation: "send_output_report",
+        })
+    }
+
+    fn send_input_report(&self, _data: &[u8]) -> HidResult<usize> {
+        Err(HidError::UnsupportedOperation {
+            operation: "send_input_report",
+        })
+    }
+
+    fn cancel_io(&self) -> HidResult<()> {
+        Err(HidError::UnsupportedOperation {
+            operation: "cancel_io",
+        })
+    }
+
+    fn poll_readable(&self, _timeout: i32) -> HidResult<bool> {
+        Err(HidError::UnsupportedOperation {
+            operation: "poll_readable",
         })
     }
+
+    fn is_connected(&self) -> bool {
+        // Asking for the report descriptor fails once the device is gone
+        self.get_report_descriptor_size().is_ok()
+    }
+}
+
+/// The type of a HID report, see [`HidDevice::set_report()`].
+#[cfg(any(feature = "std", hidapi))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ReportType {
+    Input,
+    Output,
+    Feature,
+}
+
+/// The access to request when opening a device, see [`HidApi::open_path_with_access()`].
+#[cfg(any(feature = "std", hidapi))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Access {
+    /// Only read Input reports
+    Read,
+    /// Only send Output reports
+    Write,
+    ReadWrite,
 }
 
+#[cfg(any(feature = "std", hidapi))]
 pub struct HidDevice {
     inner: Box<dyn HidDeviceBackend>,
+    blocking: Cell<bool>,
+    device_info: OnceCell<DeviceInfo>,
+    /// `None` if the report descriptor is not available
+    report_ids: OnceCell<Option<ReportIds>>,
+    strip_report_id: Cell<bool>,
+    last_report_id: Cell<Option<u8>>,
 }
 
+#[cfg(any(feature = "std", hidapi))]
 impl Debug for HidDevice {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("HidDevice").finish_non_exhaustive()
     }
 }
 
+#[cfg(any(feature = "std", hidapi))]
 impl HidDevice {
     fn from_backend(inner: Box<dyn HidDeviceBackend>) -> Self {
-        Self { inner }
+        // All backends open devices in blocking mode
+        Self {
+            inner,
+            blocking: Cell::new(true),
+            device_info: OnceCell::new(),
+            report_ids: OnceCell::new(),
+            strip_report_id: Cell::new(false),
+            last_report_id: Cell::new(None),
+        }
+    }
+
+    /// Take ownership of a device handle opened with the `hidapi` C library,
+    /// e.g. by `hid_open_path()`. The handle is closed when the `HidDevice` is dropped.
+    ///
+    /// Fails with [`HidError::NotInitialized`] if `device` is null.
+    ///
+    /// # Safety
+    ///
+    /// `device` has to be null or a handle of the `hidapi` library this crate
+    /// is linked against, which is not closed or used by anyone else afterwards.
+    #[cfg(hidapi)]
+    pub unsafe fn from_raw(device: *mut ffi::HidDevice) -> HidResult<Self> {
+        let device = hidapi::HidDevice::from_raw(device)?;
+        Ok(Self::from_backend(Box::new(device)))
+    }
+
+    /// The Report IDs of the report descriptor, read on first use.
+    ///
+    /// Returns `None` if the report descriptor can not be read.
+    fn report_ids(&self) -> Option<&ReportIds> {
+        self.report_ids
+            .get_or_init(|| {
+                let mut buf = vec![0u8; MAX_REPORT_DESCRIPTOR_SIZE];
+                match self.inner.get_report_descriptor(&mut buf) {
+                    Ok(len) if len > 0 => Some(ReportIds::parse(&buf[..len])),
+                    _ => None,
+                }
+            })
+            .as_ref()
+    }
+
+    /// Check that the report descriptor declares a report of `report_type` with `report_id`.
+    ///
+    /// Devices which do not use numbered reports only accept the Report ID 0.
+    /// If the report descriptor can not be read, every Report ID is accepted.
+    fn check_report_id(&self, report_type: ReportType, report_id: u8) -> HidResult<()> {
+        match self.report_ids() {
+            Some(ids) if !ids.contains(report_type, report_id) => {
+                Err(HidError::InvalidReportId { report_id })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The Report ID of the non-empty Input report which was just read into `buf`.
+    fn read_report_id_of(&self, buf: &[u8]) -> u8 {
+        match self.last_report_id.get() {
+            Some(id) if self.strip_report_id.get() => id,
+            // Not stripped, either because stripping is off or the descriptor is unknown
+            _ => buf[0],
+        }
+    }
+
+    /// Remove the Report ID from the Input report of `len` bytes in `buf`, if
+    /// enabled with [`HidDevice::set_strip_report_id()`].
+    ///
+    /// Returns the new length of the report.
+    fn strip_report_id(&self, buf: &mut [u8], len: usize) -> usize {
+        if !self.strip_report_id.get() {
+            return len;
+        }
+        let numbered = self
+            .report_ids()
+            .map(|ids| ids.is_numbered(ReportType::Input));
+        match numbered {
+            Some(true) if len > 0 => {
+                self.last_report_id.set(Some(buf[0]));
+                buf.copy_within(1..len, 0);
+                len - 1
+            }
+            Some(false) if len > 0 => {
+                self.last_report_id.set(Some(0));
+                len
+            }
+            _ => {
+                self.last_report_id.set(None);
+                len
+            }
+        }
     }
 }
 
 // Methods that use the backend
+#[cfg(any(feature = "std", hidapi))]
 impl HidDevice {
     /// Get the last error, which happened in the underlying hidapi C library.
     ///
@@ -520,6 +1551,18 @@ impl HidDevice {
         self.inner.check_error()
     }
 
+    /// Get the message of the last error, which happened in the underlying hidapi C library.
+    ///
+    /// Unlike [`HidDevice::check_error()`] this never fails. When the library has
+    /// no message available, `"unknown hidapi error"` is returned instead.
+    #[cfg(hidapi)]
+    pub fn last_error(&self) -> String {
+        match self.inner.check_error() {
+            Ok(HidError::HidApiError { message }) => message,
+            _ => "unknown hidapi error".to_string(),
+        }
+    }
+
     /// Write an Output report to a HID device.
     ///
     /// The first byte of `data` must contain the Report ID. For
@@ -540,15 +1583,108 @@ impl HidDevice {
         self.inner.write(data)
     }
 
+    /// Write `payload` as a sequence of Output reports with the Report ID `report_id`.
+    ///
+    /// Each report carries `chunk_size` bytes of the payload after the Report ID,
+    /// so `chunk_size` is usually the Output report length minus one. The last
+    /// report is padded with zeros, so that all reports have the same length.
+    ///
+    /// If writing a report fails, [`HidError::BatchSendFailed`] contains the
+    /// index of the failed report, together with the cause. An empty `payload`,
+    /// or a `chunk_size` of 0 or too large to add the Report ID, is rejected
+    /// with [`HidError::InvalidZeroSizeData`].
+    ///
+    /// If successful, returns the number of payload bytes written.
+    pub fn write_chunked(
+        &self,
+        report_id: u8,
+        payload: &[u8],
+        chunk_size: usize,
+    ) -> HidResult<usize> {
+        let report_len = match chunk_size.checked_add(1) {
+            Some(len) if !payload.is_empty() && chunk_size != 0 => len,
+            _ => return Err(HidError::InvalidZeroSizeData),
+        };
+
+        let mut report = vec![0u8; report_len];
+        report[0] = report_id;
+        for (index, chunk) in payload.chunks(chunk_size).enumerate() {
+            report[1..=chunk.len()].copy_from_slice(chunk);
+            report[chunk.len() + 1..].fill(0);
+            let sent = self.write(&report).and_then(|sent| {
+                if sent < report.len() {
+                    Err(HidError::IncompleteSendError {
+                        sent,
+                        all: report.len(),
+                    })
+                } else {
+                    Ok(sent)
+                }
+            });
+            sent.map_err(|e| HidError::BatchSendFailed {
+                index,
+                source: Box::new(e),
+            })?;
+        }
+        Ok(payload.len())
+    }
+
+    /// Send an Output report to a HID device using a control transfer.
+    ///
+    /// Unlike [`HidDevice::write()`], which sends the report over the
+    /// 'INTERRUPT OUT' endpoint if the device has one, this always uses a
+    /// Set_Report request on the Control endpoint. Some devices only accept
+    /// Output reports this way. The first byte of `data` must contain the
+    /// Report ID, just like for `write()`.
+    ///
+    /// Only supported by the `windows-native` and `linux-native` backends.
+    ///
+    /// If successful, returns the actual number of bytes written.
+    pub fn send_output_report(&self, data: &[u8]) -> HidResult<usize> {
+        self.inner.send_output_report(data)
+    }
+
+    /// Send a report of the given type to the device with a Set_Report request.
+    ///
+    /// Output reports are sent like with [`HidDevice::send_output_report()`]
+    /// and Feature reports like with [`HidDevice::send_feature_report()`].
+    /// Setting Input reports is only supported by the `linux-native` backend,
+    /// Windows does not offer it to user mode applications. The first byte of
+    /// `data` must contain the Report ID.
+    ///
+    /// For Output and Feature reports, the Report ID is checked against the
+    /// report descriptor, if it is available. If the descriptor does not
+    /// declare a report of this type with this ID, [`HidError::InvalidReportId`]
+    /// is returned without sending anything.
+    ///
+    /// If successful, returns the number of bytes written.
+    pub fn set_report(&self, report_type: ReportType, data: &[u8]) -> HidResult<usize> {
+        if let (ReportType::Output | ReportType::Feature, Some(&report_id)) =
+            (report_type, data.first())
+        {
+            self.check_report_id(report_type, report_id)?;
+        }
+        match report_type {
+            ReportType::Input => self.inner.send_input_report(data),
+            ReportType::Output => self.inner.send_output_report(data),
+            ReportType::Feature => {
+                self.inner.send_feature_report(data)?;
+                Ok(data.len())
+            }
+        }
+    }
+
     /// Read an Input report from a HID device.
     ///
     /// Input reports are returned to the host through the 'INTERRUPT IN'
     /// endpoint. The first byte will contain the Report number if the device
     /// uses numbered reports.
     ///
-    /// If successful, returns the actual number of bytes read.
+    /// If successful, returns the actual number of bytes read. See
+    /// [`HidDevice::set_strip_report_id()`] to always leave out the Report ID.
     pub fn read(&self, buf: &mut [u8]) -> HidResult<usize> {
-        self.inner.read(buf)
+        let len = check_report_len(self.inner.read(buf)?, buf)?;
+        Ok(self.strip_report_id(buf, len))
     }
 
     /// Read an Input report from a HID device with timeout.
@@ -558,9 +1694,157 @@ impl HidDevice {
     /// uses numbered reports. Timeout measured in milliseconds, set -1 for
     /// blocking wait.
     ///
-    /// If successful, returns the actual number of bytes read.
+    /// If successful, returns the actual number of bytes read. See
+    /// [`HidDevice::set_strip_report_id()`] to always leave out the Report ID.
     pub fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> HidResult<usize> {
-        self.inner.read_timeout(buf, timeout)
+        let len = check_report_len(self.inner.read_timeout(buf, timeout)?, buf)?;
+        Ok(self.strip_report_id(buf, len))
+    }
+
+    /// Wait until an Input report is available, without reading it.
+    ///
+    /// Returns whether a following [`HidDevice::read()`] would return a report
+    /// right away. Timeout measured in milliseconds, set -1 for blocking wait.
+    /// The report stays queued for the next read.
+    ///
+    /// Only supported by the `linux-native` and `windows-native` backends.
+    pub fn poll_readable(&self, timeout: i32) -> HidResult<bool> {
+        self.inner.poll_readable(timeout)
+    }
+
+    /// Check whether the device handle is still usable.
+    ///
+    /// A handle goes stale when the device is unplugged, or sometimes after the
+    /// system resumed from sleep, and stays that way until the device is opened
+    /// again. This sends a cheap query to the driver, without exchanging reports
+    /// with the device: the HID attributes with the `windows-native` backend,
+    /// the size of the report descriptor with `linux-native`, and the report
+    /// descriptor itself with the other backends.
+    ///
+    /// Returns `false` if the query fails, whatever the reason.
+    pub fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    /// Read an Input report from a HID device, waiting at most `timeout`.
+    ///
+    /// `None` blocks until a report is available. Timeouts longer than
+    /// `i32::MAX` milliseconds are clamped.
+    ///
+    /// If successful, returns the actual number of bytes read.
+    pub fn read_for(&self, buf: &mut [u8], timeout: Option<Duration>) -> HidResult<usize> {
+        self.read_timeout(buf, timeout_millis(timeout))
+    }
+
+    /// Collect all Input reports which arrive within `window`.
+    ///
+    /// Reports are read into buffers of `report_len` bytes until the window has
+    /// elapsed. Once the window has ended, one more report is collected if it
+    /// is already queued. Reading stops at the first error.
+    ///
+    /// If successful, returns the reports in the order they were read.
+    pub fn read_reports(&self, report_len: usize, window: Duration) -> HidResult<Vec<Vec<u8>>> {
+        let deadline = Instant::now() + window;
+        let mut reports = Vec::new();
+        let mut buf = vec![0u8; report_len];
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            // Once the window has elapsed, this turns into a non-blocking read,
+            // so a report which arrived right at the deadline is not lost
+            let len = self.read_timeout(&mut buf, timeout_millis(Some(remaining)))?;
+            if len > 0 {
+                reports.push(buf[..len].to_vec());
+            }
+            // Only a single read past the deadline, a device which keeps
+            // streaming reports would otherwise hold this call forever
+            if remaining.is_zero() {
+                break;
+            }
+        }
+
+        Ok(reports)
+    }
+
+    /// Read Input reports until one with the Report ID `report_id` arrives,
+    /// waiting at most `timeout` milliseconds in total.
+    ///
+    /// Reports with another report ID are discarded. With `None` the first
+    /// report is accepted. The Report ID is the first byte of a report, or
+    /// [`HidDevice::last_report_id()`] if [`HidDevice::set_strip_report_id()`]
+    /// is enabled. A `timeout` of -1 waits forever. Returns
+    /// [`HidError::Timeout`] if no matching report arrived in time.
+    ///
+    /// If successful, returns the number of bytes of the matching report.
+    pub fn read_matching(
+        &self,
+        buf: &mut [u8],
+        report_id: Option<u8>,
+        timeout: i32,
+    ) -> HidResult<usize> {
+        let deadline = u64::try_from(timeout)
+            .ok()
+            .map(|millis| Instant::now() + Duration::from_millis(millis));
+
+        loop {
+            let remaining =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            let len = self.read_timeout(buf, timeout_millis(remaining))?;
+            if len > 0 && report_id.is_none_or(|id| self.read_report_id_of(buf) == id) {
+                return Ok(len);
+            }
+            if remaining.is_some_and(|remaining| remaining.is_zero()) {
+                return Err(HidError::Timeout);
+            }
+        }
+    }
+
+    /// Read the next Input report with the Report ID `report_id`, discarding
+    /// all other reports, waiting at most `timeout` milliseconds in total.
+    ///
+    /// The deadline covers all reads, so a device which keeps sending other
+    /// reports can not extend it. Shorthand for
+    /// [`HidDevice::read_matching()`] with a fixed Report ID.
+    ///
+    /// If successful, returns the number of bytes of the report. This includes
+    /// the Report ID, unless [`HidDevice::set_strip_report_id()`] is enabled.
+    pub fn read_report_id(&self, report_id: u8, buf: &mut [u8], timeout: i32) -> HidResult<usize> {
+        self.read_matching(buf, Some(report_id), timeout)
+    }
+
+    /// Read an Input report into a buffer taken from `pool`, like
+    /// [`HidDevice::read_timeout()`].
+    ///
+    /// The buffer goes back to the pool when the returned report is dropped,
+    /// so reading in a loop does not allocate. If no report arrived in time,
+    /// the returned report is empty.
+    pub fn read_pooled<'p>(
+        &self,
+        pool: &'p ReportPool,
+        timeout: i32,
+    ) -> HidResult<PooledReport<'p>> {
+        let mut report = pool.take();
+        let len = self.read_timeout(report.buffer_mut(), timeout)?;
+        report.set_len(len);
+        Ok(report)
+    }
+
+    /// Write `request` and read the response to it.
+    ///
+    /// After writing, Input reports are read like with [`HidDevice::read_matching()`]
+    /// until one with the Report ID `match_report_id` arrives, waiting at most `timeout`
+    /// milliseconds. This is the usual command/response pattern of HID protocols.
+    ///
+    /// If successful, returns the number of bytes of the response.
+    pub fn transaction(
+        &self,
+        request: &[u8],
+        response: &mut [u8],
+        match_report_id: Option<u8>,
+        timeout: i32,
+    ) -> HidResult<usize> {
+        self.write(request)?;
+        self.read_matching(response, match_report_id, timeout)
     }
 
     /// Send a Feature report to the device.
@@ -581,6 +1865,76 @@ impl HidDevice {
         self.inner.send_feature_report(data)
     }
 
+    /// Send a Feature report, with the Report ID passed separately from the data.
+    ///
+    /// This builds the buffer expected by [`HidDevice::send_feature_report()`]
+    /// by prepending `report_id` to `payload`, so `payload` only contains the
+    /// report data. For devices which do not use numbered reports,
+    /// `report_id` must be 0. The 0 only marks the report as unnumbered, it
+    /// does not become part of the report data.
+    ///
+    /// If the report descriptor is available and does not declare a Feature
+    /// report with `report_id`, [`HidError::InvalidReportId`] is returned
+    /// without sending anything.
+    pub fn send_feature_report_with_id(&self, report_id: u8, payload: &[u8]) -> HidResult<()> {
+        self.check_report_id(ReportType::Feature, report_id)?;
+        let mut buf = Vec::with_capacity(payload.len() + 1);
+        buf.push(report_id);
+        buf.extend_from_slice(payload);
+        self.send_feature_report(&buf)
+    }
+
+    /// Send a Feature report and read it back to check that the device applied it.
+    ///
+    /// After sending `data`, the Feature report with the same Report ID is read
+    /// and compared to `data`. Devices which normalize some fields can be
+    /// handled with `mask`: only the bits which are set in the mask byte at the
+    /// same offset are compared. Bytes past the end of the mask are compared
+    /// completely.
+    ///
+    /// Returns whether the report read back matches the one that was sent.
+    pub fn send_and_verify_feature_report(
+        &self,
+        data: &[u8],
+        mask: Option<&[u8]>,
+    ) -> HidResult<bool> {
+        if data.is_empty() {
+            return Err(HidError::InvalidZeroSizeData);
+        }
+        self.send_feature_report(data)?;
+
+        let mut buf = vec![0u8; data.len()];
+        buf[0] = data[0];
+        let len = self.get_feature_report(&mut buf)?;
+        if len < data.len() {
+            return Ok(false);
+        }
+
+        let mask = mask.unwrap_or(&[]);
+        Ok(data.iter().zip(&buf).enumerate().all(|(i, (sent, read))| {
+            let mask = mask.get(i).copied().unwrap_or(0xff);
+            sent & mask == read & mask
+        }))
+    }
+
+    /// Send several Feature reports in order, stopping at the first failure.
+    ///
+    /// Every report has to follow the same rules as for [`HidDevice::send_feature_report()`].
+    ///
+    /// If successful, returns the number of reports that were sent. Otherwise
+    /// [`HidError::BatchSendFailed`] contains the index of the report that
+    /// failed, together with the cause.
+    pub fn send_feature_reports(&self, reports: &[&[u8]]) -> HidResult<usize> {
+        for (index, report) in reports.iter().enumerate() {
+            self.send_feature_report(report)
+                .map_err(|e| HidError::BatchSendFailed {
+                    index,
+                    source: Box::new(e),
+                })?;
+        }
+        Ok(reports.len())
+    }
+
     /// Get a feature report from a HID device.
     ///
     /// Set the first byte of `buf` to the 'Report ID' of the report to be read.
@@ -590,7 +1944,29 @@ impl HidDevice {
     /// If successful, returns the number of bytes read plus one for the report ID (which is still
     /// in the first byte).
     pub fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
-        self.inner.get_feature_report(buf)
+        check_report_len(self.inner.get_feature_report(buf)?, buf)
+    }
+
+    /// Get a Feature report, with the Report ID passed separately from the data.
+    ///
+    /// This is the counterpart of [`HidDevice::send_feature_report_with_id()`]:
+    /// `payload` only receives the report data, without the Report ID in front.
+    /// For devices which do not use numbered reports, `report_id` must be 0.
+    /// Report IDs are validated like for `send_feature_report_with_id()`.
+    ///
+    /// If successful, returns the number of bytes of report data written to `payload`.
+    pub fn get_feature_report_with_id(
+        &self,
+        report_id: u8,
+        payload: &mut [u8],
+    ) -> HidResult<usize> {
+        self.check_report_id(ReportType::Feature, report_id)?;
+        let mut buf = vec![0u8; payload.len() + 1];
+        buf[0] = report_id;
+        let len = self.get_feature_report(&mut buf)?;
+        let len = len.saturating_sub(1);
+        payload[..len].copy_from_slice(&buf[1..=len]);
+        Ok(len)
     }
 
     /// Set the device handle to be in blocking or in non-blocking mode. In
@@ -598,8 +1974,137 @@ impl HidDevice {
     /// slice if there is no data to be read. In blocking mode, `read()` will
     /// wait (block) until there is data to read before returning.
     /// Modes can be changed at any time.
-    pub fn set_blocking_mode(&self, blocking: bool) -> HidResult<()> {
-        self.inner.set_blocking_mode(blocking)
+    ///
+    /// If successful, returns the previous mode, where `true` means blocking.
+    pub fn set_blocking_mode(&self, blocking: bool) -> HidResult<bool> {
+        self.inner.set_blocking_mode(blocking)?;
+        Ok(self.blocking.replace(blocking))
+    }
+
+    /// Get the mode which was last set with [`HidDevice::set_blocking_mode()`].
+    ///
+    /// Devices are opened in blocking mode.
+    pub fn get_blocking_mode(&self) -> bool {
+        self.blocking.get()
+    }
+
+    /// Remove the Report ID from the Input reports returned by [`HidDevice::read()`]
+    /// and [`HidDevice::read_timeout()`], and the methods built on them.
+    ///
+    /// By default the first byte of a report is the Report ID if the device
+    /// uses numbered reports, and the report data otherwise. With `strip`
+    /// enabled, the buffer always starts with the report data and the Report
+    /// ID is available from [`HidDevice::last_report_id()`]. Whether the device
+    /// uses numbered reports is taken from its report descriptor. If that can
+    /// not be read, the reports are returned unchanged.
+    ///
+    /// Disabled by default. Returns the previous setting.
+    pub fn set_strip_report_id(&self, strip: bool) -> bool {
+        self.strip_report_id.replace(strip)
+    }
+
+    /// The Report ID which was removed from the last report that was read with
+    /// [`HidDevice::set_strip_report_id()`] enabled.
+    ///
+    /// This is 0 for devices which do not use numbered reports. Returns `None`
+    /// if no report was read yet, the last read returned no data or the report
+    /// descriptor of the device can not be read.
+    pub fn last_report_id(&self) -> Option<u8> {
+        self.last_report_id.get()
+    }
+
+    /// Switch to blocking or non-blocking mode until the returned guard is dropped.
+    ///
+    /// Unlike pairs of [`HidDevice::set_blocking_mode()`] calls, this also
+    /// restores the previous mode on early returns and panics.
+    pub fn blocking_mode_guard(&self, blocking: bool) -> HidResult<BlockingModeGuard<'_>> {
+        let previous = self.set_blocking_mode(blocking)?;
+        Ok(BlockingModeGuard {
+            device: self,
+            previous,
+        })
+    }
+
+    /// Discard Input reports which are already queued for the device.
+    ///
+    /// The device is temporarily switched to non-blocking mode and reports are
+    /// read until none are left, or until `max_reports` reports have been
+    /// discarded. Afterwards the previous blocking mode is restored, also when
+    /// reading fails.
+    ///
+    /// If successful, returns the number of discarded reports.
+    pub fn flush_input(&self, max_reports: usize) -> HidResult<usize> {
+        let guard = self.blocking_mode_guard(false)?;
+
+        // The reports are thrown away, so it does not matter if they get truncated
+        let mut buf = [0u8; 64];
+        let mut discarded = 0;
+        while discarded < max_reports {
+            match guard.read(&mut buf)? {
+                0 => break,
+                _ => discarded += 1,
+            }
+        }
+
+        guard.restore()?;
+        Ok(discarded)
+    }
+
+    /// Read all Input reports which are already queued for the device.
+    ///
+    /// Like [`HidDevice::flush_input()`], but the reports are returned instead
+    /// of discarded, in the order in which they arrived. Each report is read
+    /// into a buffer of `report_len` bytes. The device is temporarily switched
+    /// to non-blocking mode and the previous mode is restored afterwards, also
+    /// when reading fails.
+    ///
+    /// If successful, returns the reports, which is empty if none were queued.
+    pub fn drain_reports(&self, report_len: usize) -> HidResult<Vec<Vec<u8>>> {
+        let guard = self.blocking_mode_guard(false)?;
+
+        let mut reports = Vec::new();
+        let mut buf = vec![0u8; report_len];
+        loop {
+            match guard.read(&mut buf)? {
+                0 => break,
+                len => reports.push(buf[..len].to_vec()),
+            }
+        }
+
+        guard.restore()?;
+        Ok(reports)
+    }
+
+    /// Measure the rate at which the device sends Input reports.
+    ///
+    /// Reads `samples` reports, at least two, waiting at most `timeout_per`
+    /// milliseconds for each one, and returns the average number of reports
+    /// per second. Reports which were already queued skew the result, so
+    /// consider calling [`HidDevice::flush_input()`] first.
+    ///
+    /// Returns [`HidError::Timeout`] if a report does not arrive in time, and
+    /// 0.0 if all reports arrived at the same instant, so that no rate can be
+    /// measured.
+    pub fn measure_report_rate(&self, samples: usize, timeout_per: i32) -> HidResult<f64> {
+        let samples = samples.max(2);
+        // Only the arrival times matter, so the reports may as well be truncated
+        let mut buf = [0u8; 64];
+        let mut first = None;
+        let mut last = Instant::now();
+
+        for _ in 0..samples {
+            if self.read_timeout(&mut buf, timeout_per)? == 0 {
+                return Err(HidError::Timeout);
+            }
+            last = Instant::now();
+            first.get_or_insert(last);
+        }
+
+        let elapsed = last.duration_since(first.unwrap_or(last));
+        if elapsed.is_zero() {
+            return Ok(0.0);
+        }
+        Ok((samples - 1) as f64 / elapsed.as_secs_f64())
     }
 
     /// Get The Manufacturer String from a HID device.
@@ -618,10 +2123,58 @@ impl HidDevice {
     }
 
     /// Get a string from a HID device, based on its string index.
+    ///
+    /// Returns [`HidError::InvalidStringIndex`] if `index` does not fit into
+    /// the 0 to 255 range of USB string descriptor indices.
     pub fn get_indexed_string(&self, index: i32) -> HidResult<Option<String>> {
+        if !(0..=u8::MAX as i32).contains(&index) {
+            return Err(HidError::InvalidStringIndex { index });
+        }
         self.inner.get_indexed_string(index)
     }
 
+    /// Like [`HidDevice::get_indexed_string()`], but reads the string in the
+    /// language `lang_id` instead of the default language of the device.
+    ///
+    /// The languages a device supports can be listed with
+    /// [`HidDevice::get_supported_languages()`]. Only supported by the
+    /// `windows-native` backend.
+    pub fn get_indexed_string_lang(&self, index: i32, lang_id: u16) -> HidResult<Option<String>> {
+        if !(0..=u8::MAX as i32).contains(&index) {
+            return Err(HidError::InvalidStringIndex { index });
+        }
+        self.inner.get_indexed_string_lang(index, lang_id)
+    }
+
+    /// Get the USB language IDs (LANGIDs) the strings of the device are available in.
+    ///
+    /// The list is read from string descriptor 0. Only supported by the
+    /// `windows-native` backend.
+    pub fn get_supported_languages(&self) -> HidResult<Vec<u16>> {
+        self.inner.get_supported_languages()
+    }
+
+    /// Get all strings of a HID device, starting at string index 1.
+    ///
+    /// Indices are read in order until the first one fails or is empty. An
+    /// error is only returned if not even the first string could be read.
+    ///
+    /// USB devices do not report how many strings they have: string descriptor
+    /// 0 only lists the supported languages, and the string indices used by a
+    /// device do not have to be contiguous. Reading a missing index fails just
+    /// like a transfer error does, so the two can not be told apart.
+    pub fn get_all_indexed_strings(&self) -> HidResult<Vec<String>> {
+        let mut strings = Vec::new();
+        for index in 1..=u8::MAX as i32 {
+            match self.get_indexed_string(index) {
+                Ok(Some(string)) if !string.is_empty() => strings.push(string),
+                Err(e) if strings.is_empty() => return Err(e),
+                _ => break,
+            }
+        }
+        Ok(strings)
+    }
+
     /// Get a report descriptor from a HID device
     ///
     /// User has to provide a preallocated buffer where the descriptor will be copied to.
@@ -630,8 +2183,564 @@ impl HidDevice {
         self.inner.get_report_descriptor(buf)
     }
 
+    /// Get the size of the report descriptor in bytes.
+    ///
+    /// This allows to allocate a buffer of the exact size for
+    /// [`HidDevice::get_report_descriptor()`]. The `linux-native` backend asks
+    /// the kernel for the size. The other backends have to build or fetch the
+    /// whole descriptor to know it, so this costs as much as getting it.
+    pub fn get_report_descriptor_size(&self) -> HidResult<usize> {
+        self.inner.get_report_descriptor_size()
+    }
+
+    /// Gather everything that is known about the device in one go.
+    ///
+    /// This is meant for diagnostic tools. Fails if any of the queries fails.
+    pub fn inspect(&self) -> HidResult<DeviceInspection> {
+        let info = self.get_device_info()?;
+        let mut report_descriptor = vec![0u8; MAX_REPORT_DESCRIPTOR_SIZE];
+        let len = self.get_report_descriptor(&mut report_descriptor)?;
+        report_descriptor.truncate(len);
+
+        Ok(DeviceInspection {
+            manufacturer_string: self.get_manufacturer_string()?,
+            product_string: self.get_product_string()?,
+            serial_number: self.get_serial_number_string()?,
+            report_descriptor,
+            input_report_length: info.input_report_length,
+            output_report_length: info.output_report_length,
+            feature_report_length: info.feature_report_length,
+            info,
+        })
+    }
+
     /// Get [`DeviceInfo`] from a HID device.
+    ///
+    /// The information is only queried from the backend on the first call and
+    /// cached for the lifetime of the handle afterwards, as it does not change
+    /// while the device is open. Use [`HidDevice::refresh_device_info()`] to
+    /// query it again.
     pub fn get_device_info(&self) -> HidResult<DeviceInfo> {
-        self.inner.get_device_info()
+        if let Some(info) = self.device_info.get() {
+            return Ok(info.clone());
+        }
+        let info = self.inner.get_device_info()?;
+        Ok(self.device_info.get_or_init(|| info).clone())
+    }
+
+    /// Query the [`DeviceInfo`] from the backend again and replace the cached one.
+    pub fn refresh_device_info(&mut self) -> HidResult<DeviceInfo> {
+        let info = self.inner.get_device_info()?;
+        self.device_info = OnceCell::from(info.clone());
+        Ok(info)
+    }
+
+    /// Cancel the pending reads and writes of the device.
+    ///
+    /// A blocked read returns [`HidError::Cancelled`]. As a `HidDevice` can not
+    /// be shared between threads, this is mostly useful through
+    /// `HidWriter::cancel_io()` to wake up the thread which owns the reading
+    /// half. Only supported by the `windows-native` backend.
+    pub fn cancel_io(&self) -> HidResult<()> {
+        self.inner.cancel_io()
+    }
+
+    /// Close the HID device and report whether that succeeded.
+    ///
+    /// Dropping a `HidDevice` closes it as well, but any error is ignored there.
+    /// Note, that the `hidapi` C library does not report errors on close, so
+    /// this always succeeds with the C backends.
+    pub fn close(self) -> HidResult<()> {
+        self.inner.close()
+    }
+}
+
+/// A snapshot of a device, created by [`HidDevice::inspect()`].
+///
+/// The report lengths are the ones of [`DeviceInfo`], so they are `None`
+/// with the backends which do not provide them.
+#[cfg(any(feature = "std", hidapi))]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct DeviceInspection {
+    pub info: DeviceInfo,
+    pub manufacturer_string: Option<String>,
+    pub product_string: Option<String>,
+    pub serial_number: Option<String>,
+    pub report_descriptor: Vec<u8>,
+    pub input_report_length: Option<u16>,
+    pub output_report_length: Option<u16>,
+    pub feature_report_length: Option<u16>,
+}
+
+/// Restores the previous blocking mode of a [`HidDevice`] when dropped.
+///
+/// Created by [`HidDevice::blocking_mode_guard()`]. The guard dereferences to
+/// the device, so it can be used in its place while the mode is changed.
+/// Errors while restoring the mode are ignored on drop, use
+/// [`BlockingModeGuard::restore()`] to get them.
+#[cfg(any(feature = "std", hidapi))]
+#[must_use = "the previous blocking mode is restored as soon as the guard is dropped"]
+pub struct BlockingModeGuard<'a> {
+    device: &'a HidDevice,
+    previous: bool,
+}
+
+#[cfg(any(feature = "std", hidapi))]
+impl BlockingModeGuard<'_> {
+    /// Restore the previous blocking mode now and report whether that succeeded.
+    pub fn restore(self) -> HidResult<()> {
+        let (device, previous) = (self.device, self.previous);
+        std::mem::forget(self);
+        device.set_blocking_mode(previous).map(|_| ())
+    }
+}
+
+#[cfg(any(feature = "std", hidapi))]
+impl std::ops::Deref for BlockingModeGuard<'_> {
+    type Target = HidDevice;
+
+    fn deref(&self) -> &HidDevice {
+        self.device
+    }
+}
+
+#[cfg(any(feature = "std", hidapi))]
+impl Drop for BlockingModeGuard<'_> {
+    fn drop(&mut self) {
+        let _ = self.device.set_blocking_mode(self.previous);
+    }
+}
+
+#[cfg(any(feature = "std", hidapi))]
+impl Debug for BlockingModeGuard<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockingModeGuard")
+            .field("previous", &self.previous)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(test, any(feature = "std", hidapi)))]
+mod tests {
+    use super::*;
+
+    fn device_info(vendor_id: u16, product_id: u16, usage_page: u16, usage: u16) -> DeviceInfo {
+        DeviceInfo {
+            path: CString::new("test").unwrap(),
+            vendor_id,
+            product_id,
+            serial_number: WcharString::None,
+            release_number: 0,
+            manufacturer_string: WcharString::None,
+            product_string: WcharString::None,
+            usage_page,
+            usage,
+            usage_known: true,
+            interface_number: 0,
+            bus_type: BusType::Usb,
+            input_report_length: None,
+            output_report_length: None,
+            feature_report_length: None,
+            usb_bcd_device: None,
+            present: true,
+            friendly_name: None,
+            #[cfg(target_os = "windows")]
+            container_id: None,
+            #[cfg(target_os = "windows")]
+            parent_instance_id: None,
+            #[cfg(target_os = "windows")]
+            instance_id: None,
+        }
+    }
+
+    #[test]
+    fn test_matches_without_filters() {
+        let info = device_info(0x1050, 0x0407, 0xF1D0, 0x01);
+        assert!(info.matches(None, None, None, None));
+    }
+
+    #[test]
+    fn test_matches_with_filters() {
+        let info = device_info(0x1050, 0x0407, 0xF1D0, 0x01);
+        assert!(info.matches(Some(0x1050), Some(0x0407), Some(0xF1D0), Some(0x01)));
+        assert!(info.matches(None, None, Some(0xF1D0), Some(0x01)));
+        assert!(!info.matches(None, None, Some(0x0001), None));
+        assert!(!info.matches(Some(0x1050), Some(0x0408), None, None));
+    }
+
+    #[test]
+    fn test_release_version() {
+        let mut info = device_info(0x1050, 0x0407, 0xF1D0, 0x01);
+        info.release_number = 0x0210;
+        assert_eq!((2, 10), info.release_version());
+        assert_eq!("2.10", info.release_version_string());
+
+        info.release_number = 0x0105;
+        assert_eq!("1.05", info.release_version_string());
+    }
+
+    #[test]
+    fn test_identity_ignores_strings() {
+        use std::collections::HashSet;
+
+        let info = device_info(0x1050, 0x0407, 0xF1D0, 0x01);
+        let mut renamed = info.clone();
+        renamed.product_string = WcharString::String("Security Key".to_owned());
+        renamed.release_number = 0x0100;
+        assert_eq!(info, renamed);
+
+        let other_usage = device_info(0x1050, 0x0407, 0x0001, 0x06);
+        assert_ne!(info, other_usage);
+
+        let set: HashSet<_> = [info, renamed, other_usage].into_iter().collect();
+        assert_eq!(2, set.len());
+    }
+
+    #[test]
+    fn test_wchar_string_prefix_and_len() {
+        let raw: Vec<wchar_t> = "FW-1234".chars().map(|c| c as wchar_t).collect();
+        for string in [
+            WcharString::String("FW-1234".to_owned()),
+            WcharString::Raw(raw),
+        ] {
+            assert_eq!(7, string.char_len());
+            assert!(string.starts_with("FW-"));
+            assert!(string.starts_with(""));
+            assert!(!string.starts_with("FW-12345"));
+            assert!(!string.starts_with("HW-"));
+        }
+        assert_eq!(0, WcharString::None.char_len());
+        assert!(!WcharString::None.starts_with(""));
+    }
+
+    #[test]
+    fn test_wchar_string_eq_str() {
+        assert!(WcharString::String("SN-1".to_owned()).eq_str("SN-1"));
+        assert!(!WcharString::String("SN-1".to_owned()).eq_str("SN-2"));
+
+        let raw: Vec<wchar_t> = vec!['S' as wchar_t, 0xd800 as wchar_t];
+        assert!(WcharString::Raw(raw.clone()).eq_str("S\u{fffd}"));
+        assert!(!WcharString::Raw(raw).eq_str("S"));
+
+        assert!(!WcharString::None.eq_str(""));
+    }
+
+    #[test]
+    fn test_sort_devices() {
+        let mut other_path = device_info(0x1050, 0x0407, 0x0001, 0x02);
+        other_path.path = CString::new("a").unwrap();
+        let mut devices = vec![
+            device_info(0x1050, 0x0407, 0xf1d0, 0x01),
+            device_info(0x1050, 0x0407, 0x0001, 0x06),
+            other_path,
+        ];
+        sort_devices(&mut devices);
+        assert_eq!("a", devices[0].path_str());
+        assert_eq!(0x0001, devices[1].usage_page);
+        assert_eq!(0xf1d0, devices[2].usage_page);
+    }
+
+    #[test]
+    fn test_wchar_string_repair() {
+        // A lone high surrogate can not be decoded on any platform
+        let raw: Vec<wchar_t> = vec!['a' as wchar_t, 0xd800 as wchar_t, 'b' as wchar_t];
+        let string = WcharString::Raw(raw.clone());
+        assert_eq!("a\u{fffd}b", string.repair());
+        assert_eq!(Some(&raw[..]), string.as_raw());
+
+        let string = WcharString::String("abc".to_owned());
+        assert_eq!("abc", string.repair());
+        assert_eq!(None, string.as_raw());
+        assert_eq!("", WcharString::None.repair());
+    }
+
+    #[cfg(hidapi)]
+    #[test]
+    fn test_from_raw_null() {
+        let res = unsafe { HidDevice::from_raw(std::ptr::null_mut()) };
+        assert!(matches!(res, Err(HidError::NotInitialized)));
+    }
+
+    #[cfg(hidapi)]
+    #[test]
+    fn test_library_version() {
+        let (major, minor, patch) = HidApi::library_version();
+        let version = format!("{major}.{minor}.{patch}");
+        assert!(HidApi::library_version_str().starts_with(&version));
+    }
+
+    #[test]
+    fn test_string_to_wchars() {
+        // U+1F511 is outside of the Basic Multilingual Plane
+        let wide = core_types::string_to_wchars("SN-\u{1f511}");
+        assert_eq!(Some(&0), wide.last());
+        #[cfg(target_os = "windows")]
+        assert_eq!(&[0xd83d, 0xdd11, 0], &wide[3..]);
+        #[cfg(not(target_os = "windows"))]
+        assert_eq!(&[0x1f511, 0], &wide[3..]);
+        assert_eq!(
+            "SN-\u{1f511}",
+            core_types::wchar_to_string_lossy(&wide[..wide.len() - 1])
+        );
+    }
+
+    #[test]
+    fn test_wchar_string_try_into_string() {
+        let raw: Vec<wchar_t> = vec!['a' as wchar_t, 0xd800 as wchar_t];
+        assert_eq!("a\u{fffd}", WcharString::Raw(raw.clone()).repair());
+        assert!(matches!(
+            String::try_from(WcharString::Raw(raw)),
+            Err(HidError::FromWideCharError { wide_char: 0xd800 })
+        ));
+
+        let string = WcharString::String("abc".to_owned());
+        assert_eq!("abc", String::try_from(string).unwrap());
+        assert!(matches!(
+            String::try_from(WcharString::None),
+            Err(HidError::StringNotProvided)
+        ));
+    }
+
+    #[test]
+    fn test_bus_type_from_raw() {
+        assert_eq!(BusType::Usb, BusType::from_raw(0x01));
+        assert_eq!(BusType::Spi, BusType::from_raw(0x04));
+        assert_eq!(BusType::Other(0x05), BusType::from_raw(0x05));
+        assert_eq!(BusType::Unknown, BusType::from_raw(-1));
+        assert!(BusType::Bluetooth.is_bluetooth());
+        assert!(BusType::BluetoothLE.is_bluetooth());
+        assert!(!BusType::Usb.is_bluetooth());
+    }
+
+    #[test]
+    fn test_enumeration_warning_handler_replaces_itself() {
+        let path = CString::new("/dev/hidraw0").unwrap();
+        HidApi::set_enumeration_warning_handler(Arc::new(|_, _| {
+            HidApi::set_enumeration_warning_handler(Arc::new(|_, _| {}));
+        }));
+        // Would deadlock if the handler was called with the lock held
+        enumeration_warning(&path, EnumWarning::BusInfoFailed);
+        enumeration_warning(&path, EnumWarning::BusInfoFailed);
+    }
+
+    #[test]
+    fn test_core_error_round_trip() {
+        let errors = [
+            CoreError::HidApiErrorEmpty,
+            CoreError::InitializationError,
+            CoreError::InvalidZeroSizeData,
+            CoreError::IncompleteSendError { sent: 1, all: 2 },
+            CoreError::SetBlockingModeError { mode: "blocking" },
+            CoreError::Disconnected,
+            CoreError::DeviceNotFound,
+            CoreError::Timeout,
+            CoreError::UnsupportedBackend,
+            CoreError::UnsupportedOperation { operation: "read" },
+            CoreError::InvalidStringIndex { index: 256 },
+            CoreError::OversizedReport {
+                reported: 65,
+                buffer: 64,
+            },
+            CoreError::Cancelled,
+            CoreError::ReportUnderrun {
+                needed: 2,
+                remaining: 1,
+            },
+            CoreError::NotInitialized,
+            CoreError::InvalidReportId { report_id: 3 },
+            CoreError::StringNotProvided,
+        ];
+        for core in errors {
+            // Fails to compile if a variant is missing from the list above
+            match core {
+                CoreError::HidApiErrorEmpty
+                | CoreError::InitializationError
+                | CoreError::InvalidZeroSizeData
+                | CoreError::IncompleteSendError { .. }
+                | CoreError::SetBlockingModeError { .. }
+                | CoreError::Disconnected
+                | CoreError::DeviceNotFound
+                | CoreError::Timeout
+                | CoreError::UnsupportedBackend
+                | CoreError::UnsupportedOperation { .. }
+                | CoreError::InvalidStringIndex { .. }
+                | CoreError::OversizedReport { .. }
+                | CoreError::Cancelled
+                | CoreError::ReportUnderrun { .. }
+                | CoreError::NotInitialized
+                | CoreError::InvalidReportId { .. }
+                | CoreError::StringNotProvided => {}
+            }
+            let err = HidError::from(core);
+            assert_eq!(Some(core), err.as_core_error());
+            assert_eq!(core.to_string(), err.to_string());
+        }
+    }
+
+    #[test]
+    fn test_open_retryable_errors() {
+        assert!(is_retryable(&HidError::DeviceNotFound));
+        assert!(is_retryable(&HidError::HidApiErrorEmpty));
+        assert!(!is_retryable(&HidError::InvalidZeroSizeData));
+        assert!(!is_retryable(&HidError::UnsupportedBackend));
+
+        let io_error = |kind| HidError::IoError {
+            error: std::io::Error::from(kind),
+        };
+        assert!(is_retryable(&io_error(std::io::ErrorKind::NotFound)));
+        assert!(is_retryable(&HidError::Backend {
+            context: "CreateFileW",
+            error: std::io::ErrorKind::NotConnected.into(),
+        }));
+        assert!(!is_retryable(&io_error(
+            std::io::ErrorKind::PermissionDenied
+        )));
+        assert!(!is_retryable(&io_error(std::io::ErrorKind::InvalidInput)));
+    }
+
+    #[test]
+    fn test_backend_error_source() {
+        use std::error::Error;
+
+        let err = HidError::Backend {
+            context: "CreateFileW",
+            error: std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+        };
+        assert_eq!("CreateFileW failed", err.to_string());
+        assert!(err.source().is_some());
+        let err = std::io::Error::from(err);
+        assert_eq!(std::io::ErrorKind::PermissionDenied, err.kind());
+    }
+
+    #[test]
+    fn test_interface() {
+        let mut info = device_info(0x1234, 0x5678, 0xff00, 0x01);
+        assert_eq!(Some(0), info.interface());
+        info.interface_number = -1;
+        assert_eq!(None, info.interface());
+    }
+
+    /// A backend which returns queued Input reports and a fixed report descriptor
+    struct MockDevice {
+        reports: Mutex<std::collections::VecDeque<Vec<u8>>>,
+        descriptor: Vec<u8>,
+    }
+
+    impl HidDeviceBackendBase for MockDevice {
+        #[cfg(hidapi)]
+        fn check_error(&self) -> HidResult<HidError> {
+            Err(HidError::HidApiErrorEmpty)
+        }
+        fn write(&self, data: &[u8]) -> HidResult<usize> {
+            Ok(data.len())
+        }
+        fn read(&self, buf: &mut [u8]) -> HidResult<usize> {
+            self.read_timeout(buf, -1)
+        }
+        fn read_timeout(&self, buf: &mut [u8], _timeout: i32) -> HidResult<usize> {
+            let Some(report) = self.reports.lock().unwrap().pop_front() else {
+                return Ok(0);
+            };
+            buf[..report.len()].copy_from_slice(&report);
+            Ok(report.len())
+        }
+        fn send_feature_report(&self, _data: &[u8]) -> HidResult<()> {
+            Ok(())
+        }
+        fn get_feature_report(&self, _buf: &mut [u8]) -> HidResult<usize> {
+            Ok(0)
+        }
+        fn set_blocking_mode(&self, _blocking: bool) -> HidResult<()> {
+            Ok(())
+        }
+        fn get_device_info(&self) -> HidResult<DeviceInfo> {
+            Ok(device_info(0x1234, 0x5678, 0xff00, 0x01))
+        }
+        fn get_manufacturer_string(&self) -> HidResult<Option<String>> {
+            Ok(None)
+        }
+        fn get_product_string(&self) -> HidResult<Option<String>> {
+            Ok(None)
+        }
+        fn get_serial_number_string(&self) -> HidResult<Option<String>> {
+            Ok(None)
+        }
+        fn get_report_descriptor(&self, buf: &mut [u8]) -> HidResult<usize> {
+            buf[..self.descriptor.len()].copy_from_slice(&self.descriptor);
+            Ok(self.descriptor.len())
+        }
+        fn close(self: Box<Self>) -> HidResult<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    impl HidDeviceBackendWindows for MockDevice {
+        fn get_container_id(&self) -> HidResult<GUID> {
+            Err(HidError::UnsupportedOperation {
+                operation: "get_container_id",
+            })
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    impl HidDeviceBackendMacos for MockDevice {
+        fn get_location_id(&self) -> HidResult<u32> {
+            Ok(0)
+        }
+        fn is_open_exclusive(&self) -> HidResult<bool> {
+            Ok(false)
+        }
+    }
+
+    #[test]
+    fn test_read_matching_strip_report_id() {
+        // Input reports with Report ID 1 and 2
+        let descriptor = vec![
+            0x06, 0x00, 0xff, 0x09, 0x01, 0xa1, 0x01, 0x75, 0x08, 0x95, 0x02, 0x85, 0x01, 0x81,
+            0x02, 0x85, 0x02, 0x81, 0x02, 0xc0,
+        ];
+        let reports = [vec![2, 1, 9], vec![1, 7, 8]];
+        let device = HidDevice::from_backend(Box::new(MockDevice {
+            reports: Mutex::new(reports.iter().cloned().collect()),
+            descriptor,
+        }));
+        device.set_strip_report_id(true);
+
+        // The payload of the first report starts with 1, which must not count as its Report ID
+        let mut buf = [0u8; 8];
+        assert_eq!(2, device.read_report_id(1, &mut buf, 1000).unwrap());
+        assert_eq!(&[7, 8], &buf[..2]);
+        assert_eq!(Some(1), device.last_report_id());
+    }
+
+    #[test]
+    fn test_read_reports_bounded_after_window() {
+        let device = HidDevice::from_backend(Box::new(MockDevice {
+            reports: Mutex::new((0..5).map(|i| vec![i]).collect()),
+            descriptor: Vec::new(),
+        }));
+
+        // Queued reports must not keep the call going past the window
+        let reports = device.read_reports(8, Duration::ZERO).unwrap();
+        assert_eq!(vec![vec![0]], reports);
+        let reports = device.read_reports(8, Duration::ZERO).unwrap();
+        assert_eq!(vec![vec![1]], reports);
+    }
+
+    #[test]
+    fn test_write_chunked_invalid_chunk_size() {
+        let device = HidDevice::from_backend(Box::new(MockDevice {
+            reports: Mutex::new(Default::default()),
+            descriptor: Vec::new(),
+        }));
+        for chunk_size in [0, usize::MAX] {
+            assert!(matches!(
+                device.write_chunked(1, &[1, 2, 3], chunk_size),
+                Err(HidError::InvalidZeroSizeData)
+            ));
+        }
+        assert_eq!(3, device.write_chunked(1, &[1, 2, 3], 2).unwrap());
     }
 }
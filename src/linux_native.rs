@@ -8,7 +8,7 @@ use std::{
     fs::{File, OpenOptions},
     io::{Cursor, Read, Seek, SeekFrom},
     os::{
-        fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd},
+        fd::{AsFd, AsRawFd, BorrowedFd, IntoRawFd, OwnedFd},
         unix::{ffi::OsStringExt, fs::OpenOptionsExt},
     },
     path::{Path, PathBuf},
@@ -18,11 +18,14 @@ use nix::{
     errno::Errno,
     poll::{poll, PollFd, PollFlags},
     sys::stat::{fstat, major, minor},
-    unistd::{read, write},
+    unistd::{close, read, write},
 };
 
-use super::{BusType, DeviceInfo, HidDeviceBackendBase, HidError, HidResult, WcharString};
-use ioctl::{hidraw_ioc_get_feature, hidraw_ioc_grdescsize, hidraw_ioc_set_feature};
+use super::{Access, BusType, DeviceInfo, HidDeviceBackendBase, HidError, HidResult, WcharString};
+use ioctl::{
+    hidraw_ioc_get_feature, hidraw_ioc_grdescsize, hidraw_ioc_set_feature, hidraw_ioc_set_input,
+    hidraw_ioc_set_output,
+};
 
 // Bus values from linux/input.h
 const BUS_USB: u16 = 0x03;
@@ -34,30 +37,41 @@ pub struct HidApiBackend;
 
 impl HidApiBackend {
     pub fn get_hid_device_info_vector(vid: u16, pid: u16) -> HidResult<Vec<DeviceInfo>> {
+        let mut devices = Vec::new();
+        Self::enumerate_with(vid, pid, |info| devices.push(info))?;
+        Ok(devices)
+    }
+
+    pub fn get_hid_device_info_vector_for_bus(bus: BusType) -> HidResult<Vec<DeviceInfo>> {
+        let mut devices = Vec::new();
+        Self::enumerate_with(0, 0, |info| {
+            if info.bus_type == bus {
+                devices.push(info)
+            }
+        })?;
+        Ok(devices)
+    }
+
+    pub fn enumerate_with(vid: u16, pid: u16, f: impl FnMut(DeviceInfo)) -> HidResult<()> {
         // The C version assumes these can't fail, and they should only fail in case
         // of memory allocation issues, at which point maybe we should panic
         let mut enumerator = match udev::Enumerator::new() {
             Ok(e) => e,
-            Err(_) => return Ok(Vec::new()),
+            Err(_) => return Ok(()),
         };
         enumerator.match_subsystem("hidraw").unwrap();
         let scan = match enumerator.scan_devices() {
             Ok(s) => s,
-            Err(_) => return Ok(Vec::new()),
+            Err(_) => return Ok(()),
         };
 
-        let devices = scan
-            .filter_map(|device| device_to_hid_device_info(&device))
+        scan.filter_map(|device| device_to_hid_device_info(&device))
             .flatten()
             .filter(|device| vid == 0 || device.vendor_id == vid)
             .filter(|device| pid == 0 || device.product_id == pid)
-            .collect::<Vec<_>>();
+            .for_each(f);
 
-        Ok(devices)
-    }
-
-    pub fn open(vid: u16, pid: u16) -> HidResult<HidDevice> {
-        HidDevice::open(vid, pid, None)
+        Ok(())
     }
 
     pub fn open_serial(vid: u16, pid: u16, sn: &str) -> HidResult<HidDevice> {
@@ -67,6 +81,10 @@ impl HidApiBackend {
     pub fn open_path(device_path: &CStr) -> HidResult<HidDevice> {
         HidDevice::open_path(device_path)
     }
+
+    pub fn open_path_with_access(device_path: &CStr, access: Access) -> HidResult<HidDevice> {
+        HidDevice::open_path_with_access(device_path, access)
+    }
 }
 
 fn device_to_hid_device_info(raw_device: &udev::Device) -> Option<Vec<DeviceInfo>> {
@@ -122,8 +140,15 @@ fn device_to_hid_device_info(raw_device: &udev::Device) -> Option<Vec<DeviceInfo
         product_string: WcharString::None,
         usage_page: 0,
         usage: 0,
+        usage_known: false,
         interface_number: -1,
         bus_type,
+        input_report_length: None,
+        output_report_length: None,
+        feature_report_length: None,
+        usb_bcd_device: None,
+        present: true,
+        friendly_name: None,
     };
 
     // USB has a bunch more information but everything else gets the same empty
@@ -145,6 +170,7 @@ fn device_to_hid_device_info(raw_device: &udev::Device) -> Option<Vec<DeviceInfo
             infos.push(DeviceInfo {
                 usage_page,
                 usage,
+                usage_known: true,
                 ..info
             });
 
@@ -180,7 +206,7 @@ fn fill_in_usb(device: &udev::Device, info: DeviceInfo, name: &OsStr) -> DeviceI
     };
     let manufacturer_string = attribute_as_wchar(&usb_dev, "manufacturer");
     let product_string = attribute_as_wchar(&usb_dev, "product");
-    let release_number = attribute_as_u16(&usb_dev, "bcdDevice").unwrap_or(0);
+    let usb_bcd_device = attribute_as_u16(&usb_dev, "bcdDevice");
     let interface_number = device
         .parent_with_subsystem_devtype("usb", "usb_interface")
         .ok()
@@ -189,7 +215,8 @@ fn fill_in_usb(device: &udev::Device, info: DeviceInfo, name: &OsStr) -> DeviceI
         .unwrap_or(-1);
 
     DeviceInfo {
-        release_number,
+        release_number: usb_bcd_device.unwrap_or(0),
+        usb_bcd_device,
         manufacturer_string,
         product_string,
         interface_number,
@@ -417,30 +444,34 @@ impl HidDevice {
             .iter()
             .filter(|device| device.vendor_id == vid && device.product_id == pid)
         {
-            match (sn, &device.serial_number) {
-                (None, _) => return Self::open_path(&device.path),
-                (Some(sn), WcharString::String(serial_number)) if sn == serial_number => {
-                    return Self::open_path(&device.path)
-                }
-                _ => continue,
-            };
+            if sn.is_none_or(|sn| device.serial_number.eq_str(sn)) {
+                return Self::open_path(&device.path);
+            }
         }
 
-        Err(HidError::HidApiError {
-            message: "device not found".into(),
-        })
+        Err(HidError::DeviceNotFound)
     }
 
     pub(crate) fn open_path(device_path: &CStr) -> HidResult<HidDevice> {
+        Self::open_path_with_access(device_path, Access::ReadWrite)
+    }
+
+    pub(crate) fn open_path_with_access(
+        device_path: &CStr,
+        access: Access,
+    ) -> HidResult<HidDevice> {
         // Paths on Linux can be anything but devnode paths are going to be ASCII
         let path = device_path.to_str().expect("path must be utf-8");
         let fd: OwnedFd = match OpenOptions::new()
-            .read(true)
-            .write(true)
+            .read(access != Access::Write)
+            .write(access != Access::Read)
             .custom_flags(libc::O_CLOEXEC | libc::O_NONBLOCK)
             .open(path)
         {
             Ok(f) => f.into(),
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                return Err(HidError::IoError { error: e });
+            }
             Err(e) => {
                 return Err(HidError::HidApiError {
                     message: format!("failed to open device with path {path}: {e}"),
@@ -507,9 +538,7 @@ impl HidDeviceBackendBase for HidDevice {
             .map(|e| e.intersects(PollFlags::POLLERR | PollFlags::POLLHUP | PollFlags::POLLNVAL));
 
         if events.is_none() || events == Some(true) {
-            return Err(HidError::HidApiError {
-                message: "unexpected poll error (device disconnected)".into(),
-            });
+            return Err(HidError::Disconnected);
         }
 
         match read(self.fd.as_raw_fd(), buf) {
@@ -550,6 +579,52 @@ impl HidDeviceBackendBase for HidDevice {
         Ok(())
     }
 
+    fn send_output_report(&self, data: &[u8]) -> HidResult<usize> {
+        if data.is_empty() {
+            return Err(HidError::InvalidZeroSizeData);
+        }
+
+        // Same as for feature reports, the ioctl needs a mutable buffer
+        let mut d = data.to_vec();
+
+        match unsafe { hidraw_ioc_set_output(self.fd.as_raw_fd(), &mut d) } {
+            Ok(n) => Ok(n as usize),
+            Err(e) => Err(HidError::HidApiError {
+                message: format!("ioctl (SOUTPUT): {e}"),
+            }),
+        }
+    }
+
+    fn send_input_report(&self, data: &[u8]) -> HidResult<usize> {
+        if data.is_empty() {
+            return Err(HidError::InvalidZeroSizeData);
+        }
+
+        let mut d = data.to_vec();
+
+        match unsafe { hidraw_ioc_set_input(self.fd.as_raw_fd(), &mut d) } {
+            Ok(n) => Ok(n as usize),
+            Err(e) => Err(HidError::HidApiError {
+                message: format!("ioctl (SINPUT): {e}"),
+            }),
+        }
+    }
+
+    fn poll_readable(&self, timeout: i32) -> HidResult<bool> {
+        let pollfd = PollFd::new(&self.fd, PollFlags::POLLIN);
+        if poll(&mut [pollfd], timeout)? == 0 {
+            return Ok(false);
+        }
+
+        let events = pollfd
+            .revents()
+            .map(|e| e.intersects(PollFlags::POLLERR | PollFlags::POLLHUP | PollFlags::POLLNVAL));
+        if events.is_none() || events == Some(true) {
+            return Err(HidError::Disconnected);
+        }
+        Ok(true)
+    }
+
     fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
         let res = match unsafe { hidraw_ioc_get_feature(self.fd.as_raw_fd(), buf) } {
             Ok(n) => n as usize,
@@ -609,6 +684,22 @@ impl HidDeviceBackendBase for HidDevice {
         buf[..min_size].copy_from_slice(&descriptor.0[..min_size]);
         Ok(min_size)
     }
+
+    fn get_report_descriptor_size(&self) -> HidResult<usize> {
+        let mut size = 0_i32;
+        if let Err(e) = unsafe { hidraw_ioc_grdescsize(self.fd.as_raw_fd(), &mut size) } {
+            return Err(HidError::HidApiError {
+                message: format!("ioctl(GRDESCSIZE): {e}"),
+            });
+        }
+        Ok(size as usize)
+    }
+
+    fn close(self: Box<Self>) -> HidResult<()> {
+        // Dropping the `OwnedFd` makes the same call, but ignores the error
+        close(self.fd.into_raw_fd())?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -7,6 +7,8 @@ const HIDRAW_IOC_MAGIC: u8 = b'H';
 const HIDRAW_IOC_GRDESCSIZE: u8 = 0x01;
 const HIDRAW_SET_FEATURE: u8 = 0x06;
 const HIDRAW_GET_FEATURE: u8 = 0x07;
+const HIDRAW_SET_INPUT: u8 = 0x09;
+const HIDRAW_SET_OUTPUT: u8 = 0x0B;
 
 ioctl_read!(
     hidraw_ioc_grdescsize,
@@ -27,3 +29,10 @@ ioctl_readwrite_buf!(
     HIDRAW_GET_FEATURE,
     u8
 );
+ioctl_readwrite_buf!(hidraw_ioc_set_input, HIDRAW_IOC_MAGIC, HIDRAW_SET_INPUT, u8);
+ioctl_readwrite_buf!(
+    hidraw_ioc_set_output,
+    HIDRAW_IOC_MAGIC,
+    HIDRAW_SET_OUTPUT,
+    u8
+);
@@ -0,0 +1,152 @@
+//! Reusable buffers for reading reports without allocating
+
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Mutex;
+
+/// A pool of fixed-size buffers for [`HidDevice::read_pooled()`](crate::HidDevice::read_pooled).
+///
+/// Buffers are only allocated when the pool is empty. Every [`PooledReport`]
+/// returns its buffer when it is dropped, so a read loop which drops its
+/// reports allocates nothing once the pool is warmed up. The pool can be
+/// shared between threads.
+///
+/// ```rust
+/// use hidapi::ReportPool;
+///
+/// let pool = ReportPool::new(64);
+/// let report = pool.take();
+/// assert_eq!(0, pool.available());
+/// drop(report);
+/// assert_eq!(1, pool.available());
+/// ```
+pub struct ReportPool {
+    buffer_len: usize,
+    free: Mutex<Vec<Box<[u8]>>>,
+}
+
+impl ReportPool {
+    /// Create an empty pool which hands out buffers of `buffer_len` bytes.
+    ///
+    /// `buffer_len` should be the size of the largest Input report.
+    pub fn new(buffer_len: usize) -> Self {
+        Self::with_buffers(buffer_len, 0)
+    }
+
+    /// Create a pool which allocates `count` buffers upfront.
+    pub fn with_buffers(buffer_len: usize, count: usize) -> Self {
+        let free = (0..count)
+            .map(|_| vec![0u8; buffer_len].into_boxed_slice())
+            .collect();
+        Self {
+            buffer_len,
+            free: Mutex::new(free),
+        }
+    }
+
+    /// The size of the buffers of this pool.
+    pub fn buffer_len(&self) -> usize {
+        self.buffer_len
+    }
+
+    /// The number of buffers which are ready to be handed out.
+    pub fn available(&self) -> usize {
+        self.free.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Take a buffer out of the pool, allocating a new one if the pool is empty.
+    ///
+    /// The report is empty until data is read into it.
+    pub fn take(&self) -> PooledReport<'_> {
+        let buf = self
+            .free
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop()
+            .unwrap_or_else(|| vec![0u8; self.buffer_len].into_boxed_slice());
+        PooledReport {
+            pool: self,
+            buf,
+            len: 0,
+        }
+    }
+
+    fn put(&self, buf: Box<[u8]>) {
+        self.free
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(buf);
+    }
+}
+
+impl fmt::Debug for ReportPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReportPool")
+            .field("buffer_len", &self.buffer_len)
+            .field("available", &self.available())
+            .finish()
+    }
+}
+
+/// A report read into a buffer of a [`ReportPool`].
+///
+/// Dereferences to the bytes of the report. The buffer goes back to the pool
+/// when the report is dropped.
+pub struct PooledReport<'a> {
+    pool: &'a ReportPool,
+    buf: Box<[u8]>,
+    len: usize,
+}
+
+impl PooledReport<'_> {
+    /// The whole buffer, to read a report into.
+    pub(crate) fn buffer_mut(&mut self) -> &mut [u8] {
+        &mut self.buf
+    }
+
+    /// Set the number of bytes of the buffer which belong to the report.
+    pub(crate) fn set_len(&mut self, len: usize) {
+        self.len = len.min(self.buf.len());
+    }
+}
+
+impl Deref for PooledReport<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl Drop for PooledReport<'_> {
+    fn drop(&mut self) {
+        self.pool.put(std::mem::take(&mut self.buf));
+    }
+}
+
+impl fmt::Debug for PooledReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PooledReport").field(&&**self).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffers_are_reused() {
+        let pool = ReportPool::with_buffers(8, 1);
+        let mut report = pool.take();
+        report.buffer_mut()[..2].copy_from_slice(&[1, 2]);
+        report.set_len(2);
+        assert_eq!(&[1, 2], &*report);
+        let ptr = report.as_ptr();
+        drop(report);
+
+        assert_eq!(1, pool.available());
+        let report = pool.take();
+        assert_eq!(ptr, report.buf.as_ptr());
+        assert!(report.is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+//! Helpers for parsing the fields of a report
+
+use crate::{HidError, HidResult, ReportType};
+
+/// A cursor over the bytes of a report, for reading multi-byte fields with
+/// an explicit byte order.
+///
+/// Every read advances the cursor. Reading past the end of the report fails
+/// with [`HidError::ReportUnderrun`] and leaves the cursor where it was.
+///
+/// ```rust
+/// use hidapi::ReportReader;
+///
+/// let report = [0x01, 0x34, 0x12, 0xff, 0xfe];
+/// let mut reader = ReportReader::new(&report);
+/// assert_eq!(0x01, reader.u8().unwrap());
+/// assert_eq!(0x1234, reader.u16_le().unwrap());
+/// assert_eq!(-2, reader.i16_be().unwrap());
+/// assert!(reader.u8().is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReportReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ReportReader<'a> {
+    /// Create a reader which starts at the first byte of `buf`.
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// The offset of the next byte to be read.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// The number of bytes which are left to read.
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    /// Read the next `n` bytes.
+    pub fn bytes(&mut self, n: usize) -> HidResult<&'a [u8]> {
+        let remaining = self.remaining();
+        if n > remaining {
+            return Err(HidError::ReportUnderrun {
+                needed: n,
+                remaining,
+            });
+        }
+        let bytes = &self.buf[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    /// Skip the next `n` bytes.
+    pub fn skip(&mut self, n: usize) -> HidResult<()> {
+        self.bytes(n).map(|_| ())
+    }
+
+    fn array<const N: usize>(&mut self) -> HidResult<[u8; N]> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+
+    pub fn u8(&mut self) -> HidResult<u8> {
+        self.array().map(u8::from_le_bytes)
+    }
+
+    pub fn i8(&mut self) -> HidResult<i8> {
+        self.array().map(i8::from_le_bytes)
+    }
+
+    pub fn u16_le(&mut self) -> HidResult<u16> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    pub fn u16_be(&mut self) -> HidResult<u16> {
+        self.array().map(u16::from_be_bytes)
+    }
+
+    pub fn i16_le(&mut self) -> HidResult<i16> {
+        self.array().map(i16::from_le_bytes)
+    }
+
+    pub fn i16_be(&mut self) -> HidResult<i16> {
+        self.array().map(i16::from_be_bytes)
+    }
+
+    pub fn u32_le(&mut self) -> HidResult<u32> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    pub fn u32_be(&mut self) -> HidResult<u32> {
+        self.array().map(u32::from_be_bytes)
+    }
+
+    pub fn i32_le(&mut self) -> HidResult<i32> {
+        self.array().map(i32::from_le_bytes)
+    }
+
+    pub fn i32_be(&mut self) -> HidResult<i32> {
+        self.array().map(i32::from_be_bytes)
+    }
+}
+
+/// The Report IDs which a report descriptor declares, per report type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ReportIds {
+    input: Vec<u8>,
+    output: Vec<u8>,
+    feature: Vec<u8>,
+}
+
+impl ReportIds {
+    /// Collect the Report IDs of all Input, Output and Feature main items.
+    ///
+    /// Reports of descriptors without Report ID items get the ID 0, which
+    /// marks them as unnumbered.
+    pub(crate) fn parse(descriptor: &[u8]) -> Self {
+        let mut ids = Self::default();
+        let mut report_id = 0;
+        // Push and Pop save and restore the global items, which include the Report ID
+        let mut stack = Vec::new();
+
+        let mut pos = 0;
+        while pos < descriptor.len() {
+            let prefix = descriptor[pos];
+            if prefix == 0xfe {
+                // Long item: the size of its data follows the prefix, then the long tag
+                let size = descriptor.get(pos + 1).copied().unwrap_or(0) as usize;
+                pos += 3 + size;
+                continue;
+            }
+            let size = match prefix & 0x03 {
+                3 => 4,
+                size => size as usize,
+            };
+            let Some(data) = descriptor.get(pos + 1..pos + 1 + size) else {
+                break;
+            };
+            // Tag and type of the item, without the size bits
+            match prefix & 0xfc {
+                0x80 => ids.add(ReportType::Input, report_id),
+                0x90 => ids.add(ReportType::Output, report_id),
+                0xb0 => ids.add(ReportType::Feature, report_id),
+                0x84 => report_id = data.first().copied().unwrap_or(0),
+                0xa4 => stack.push(report_id),
+                0xb4 => report_id = stack.pop().unwrap_or(report_id),
+                _ => (),
+            }
+            pos += 1 + size;
+        }
+        ids
+    }
+
+    fn ids(&self, report_type: ReportType) -> &Vec<u8> {
+        match report_type {
+            ReportType::Input => &self.input,
+            ReportType::Output => &self.output,
+            ReportType::Feature => &self.feature,
+        }
+    }
+
+    fn add(&mut self, report_type: ReportType, report_id: u8) {
+        let ids = match report_type {
+            ReportType::Input => &mut self.input,
+            ReportType::Output => &mut self.output,
+            ReportType::Feature => &mut self.feature,
+        };
+        if !ids.contains(&report_id) {
+            ids.push(report_id);
+        }
+    }
+
+    /// Whether the reports of `report_type` start with a Report ID.
+    pub(crate) fn is_numbered(&self, report_type: ReportType) -> bool {
+        self.ids(report_type).iter().any(|&id| id != 0)
+    }
+
+    /// Whether a report of `report_type` with `report_id` is declared.
+    pub(crate) fn contains(&self, report_type: ReportType, report_id: u8) -> bool {
+        self.ids(report_type).contains(&report_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_fields() {
+        let report = [0x02, 0xff, 0x34, 0x12, 0x12, 0x34, 0x78, 0x56, 0x34, 0x12];
+        let mut reader = ReportReader::new(&report);
+        assert_eq!(2, reader.u8().unwrap());
+        assert_eq!(-1, reader.i8().unwrap());
+        assert_eq!(0x1234, reader.u16_le().unwrap());
+        assert_eq!(0x1234, reader.u16_be().unwrap());
+        assert_eq!(0x12345678, reader.u32_le().unwrap());
+        assert_eq!(0, reader.remaining());
+    }
+
+    #[test]
+    fn test_underrun() {
+        let report = [0x01, 0x02, 0x03];
+        let mut reader = ReportReader::new(&report);
+        reader.skip(2).unwrap();
+        assert!(matches!(
+            reader.u16_le(),
+            Err(HidError::ReportUnderrun {
+                needed: 2,
+                remaining: 1
+            })
+        ));
+        // A failed read does not move the cursor
+        assert_eq!(2, reader.position());
+        assert_eq!(&[0x03], reader.bytes(1).unwrap());
+    }
+
+    #[test]
+    fn test_report_ids() {
+        // Vendor defined collection with an unnumbered Input and Feature report
+        let unnumbered = [
+            0x06, 0x00, 0xff, 0x09, 0x01, 0xa1, 0x01, 0x75, 0x08, 0x95, 0x08, 0x81, 0x02, 0xb1,
+            0x02, 0xc0,
+        ];
+        let ids = ReportIds::parse(&unnumbered);
+        assert!(ids.contains(ReportType::Input, 0));
+        assert!(ids.contains(ReportType::Feature, 0));
+        assert!(!ids.contains(ReportType::Feature, 1));
+        assert!(!ids.contains(ReportType::Output, 0));
+        assert!(!ids.is_numbered(ReportType::Input));
+
+        // Report ID 1 with an Input and Output report, Report ID 2 with a Feature report
+        let numbered = [
+            0x06, 0x00, 0xff, 0x09, 0x01, 0xa1, 0x01, 0x85, 0x01, 0x75, 0x08, 0x95, 0x08, 0x81,
+            0x02, 0xa4, 0x85, 0x02, 0xb1, 0x02, 0xb4, 0x91, 0x02, 0xc0,
+        ];
+        let ids = ReportIds::parse(&numbered);
+        assert!(ids.contains(ReportType::Input, 1));
+        assert!(!ids.contains(ReportType::Input, 0));
+        assert!(ids.contains(ReportType::Feature, 2));
+        assert!(!ids.contains(ReportType::Feature, 1));
+        // Pop restored Report ID 1
+        assert!(ids.contains(ReportType::Output, 1));
+        assert!(!ids.contains(ReportType::Output, 2));
+        assert!(ids.is_numbered(ReportType::Input));
+    }
+}
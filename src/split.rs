@@ -0,0 +1,115 @@
+//! Separate read and write halves of a [`HidDevice`]
+
+use std::fmt::{self, Debug};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{HidDevice, HidResult};
+
+struct SharedDevice(HidDevice);
+
+// SAFETY: The device is only reachable through one `HidReader` and one `HidWriter`.
+// The reader only calls the read methods and the writer only the write and feature
+// report methods. The native backends keep separate state for reading, writing
+// and feature reports, so the halves never touch the same `Cell`/`RefCell`, and
+// the OS handles allow concurrent reads and writes:
+// - On Windows every kind of transfer uses its own `OVERLAPPED` structure.
+// - On Linux `read(2)`, `write(2)` and `ioctl(2)` can be used concurrently on an fd.
+// `HidWriter::cancel_io()` only passes the OS handle to `CancelIoEx`, which is
+// meant to be called from another thread than the one doing the I/O.
+// Neither half exposes `get_device_info()`, so the cached device info is never shared.
+unsafe impl Sync for SharedDevice {}
+
+impl Debug for SharedDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The reading half of a [`HidDevice`], created by [`HidDevice::split()`].
+#[derive(Debug)]
+pub struct HidReader {
+    shared: Arc<SharedDevice>,
+}
+
+/// The writing half of a [`HidDevice`], created by [`HidDevice::split()`].
+#[derive(Debug)]
+pub struct HidWriter {
+    shared: Arc<SharedDevice>,
+}
+
+impl HidDevice {
+    /// Split the device into a reading and a writing half.
+    ///
+    /// Both halves can be moved to different threads, so that reading and
+    /// writing can happen at the same time without locking. The device is
+    /// closed once both halves are dropped.
+    ///
+    /// Only available with the native backends. The `hidapi` C library keeps a
+    /// single error string per device, which is not safe to update from
+    /// multiple threads.
+    pub fn split(self) -> (HidReader, HidWriter) {
+        let shared = Arc::new(SharedDevice(self));
+        (
+            HidReader {
+                shared: shared.clone(),
+            },
+            HidWriter { shared },
+        )
+    }
+}
+
+impl HidReader {
+    /// See [`HidDevice::read()`]
+    pub fn read(&self, buf: &mut [u8]) -> HidResult<usize> {
+        self.shared.0.read(buf)
+    }
+
+    /// See [`HidDevice::read_timeout()`]
+    pub fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> HidResult<usize> {
+        self.shared.0.read_timeout(buf, timeout)
+    }
+
+    /// See [`HidDevice::read_for()`]
+    pub fn read_for(&self, buf: &mut [u8], timeout: Option<Duration>) -> HidResult<usize> {
+        self.shared.0.read_for(buf, timeout)
+    }
+
+    /// See [`HidDevice::set_blocking_mode()`]
+    pub fn set_blocking_mode(&self, blocking: bool) -> HidResult<bool> {
+        self.shared.0.set_blocking_mode(blocking)
+    }
+
+    /// See [`HidDevice::get_blocking_mode()`]
+    pub fn get_blocking_mode(&self) -> bool {
+        self.shared.0.get_blocking_mode()
+    }
+}
+
+impl HidWriter {
+    /// See [`HidDevice::write()`]
+    pub fn write(&self, data: &[u8]) -> HidResult<usize> {
+        self.shared.0.write(data)
+    }
+
+    /// See [`HidDevice::send_output_report()`]
+    pub fn send_output_report(&self, data: &[u8]) -> HidResult<usize> {
+        self.shared.0.send_output_report(data)
+    }
+
+    /// See [`HidDevice::send_feature_report()`]
+    pub fn send_feature_report(&self, data: &[u8]) -> HidResult<()> {
+        self.shared.0.send_feature_report(data)
+    }
+
+    /// See [`HidDevice::get_feature_report()`]
+    pub fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        self.shared.0.get_feature_report(buf)
+    }
+
+    /// Cancel the pending I/O of the device, which wakes up a blocked
+    /// [`HidReader`]. See [`HidDevice::cancel_io()`]
+    pub fn cancel_io(&self) -> HidResult<()> {
+        self.shared.0.cancel_io()
+    }
+}
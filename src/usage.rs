@@ -0,0 +1,103 @@
+//! Typed HID usage pages and usages
+//!
+//! The values are taken from the [HID Usage Tables](https://usb.org/document-library/hid-usage-tables-14).
+
+use std::fmt::{Display, Formatter, Result};
+
+/// A HID usage page.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UsagePage(pub u16);
+
+impl UsagePage {
+    pub const GENERIC_DESKTOP: UsagePage = UsagePage(0x01);
+    pub const SIMULATION: UsagePage = UsagePage(0x02);
+    pub const KEYBOARD: UsagePage = UsagePage(0x07);
+    pub const LED: UsagePage = UsagePage(0x08);
+    pub const BUTTON: UsagePage = UsagePage(0x09);
+    pub const CONSUMER: UsagePage = UsagePage(0x0C);
+    pub const DIGITIZER: UsagePage = UsagePage(0x0D);
+    pub const FIDO: UsagePage = UsagePage(0xF1D0);
+    /// First usage page of the vendor defined range
+    pub const VENDOR_DEFINED_START: UsagePage = UsagePage(0xFF00);
+    /// Last usage page of the vendor defined range
+    pub const VENDOR_DEFINED_END: UsagePage = UsagePage(0xFFFF);
+
+    /// Check whether the usage page is in the vendor defined range.
+    pub fn is_vendor_defined(self) -> bool {
+        self >= Self::VENDOR_DEFINED_START
+    }
+
+    fn name(self) -> Option<&'static str> {
+        match self {
+            Self::GENERIC_DESKTOP => Some("Generic Desktop"),
+            Self::SIMULATION => Some("Simulation Controls"),
+            Self::KEYBOARD => Some("Keyboard/Keypad"),
+            Self::LED => Some("LED"),
+            Self::BUTTON => Some("Button"),
+            Self::CONSUMER => Some("Consumer"),
+            Self::DIGITIZER => Some("Digitizers"),
+            Self::FIDO => Some("FIDO Alliance"),
+            _ => None,
+        }
+    }
+}
+
+impl Display for UsagePage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self.name() {
+            Some(name) => write!(f, "{} ({:#06x})", name, self.0),
+            None if self.is_vendor_defined() => write!(f, "Vendor Defined ({:#06x})", self.0),
+            None => write!(f, "{:#06x}", self.0),
+        }
+    }
+}
+
+impl From<u16> for UsagePage {
+    fn from(value: u16) -> Self {
+        Self(value)
+    }
+}
+
+impl From<UsagePage> for u16 {
+    fn from(value: UsagePage) -> Self {
+        value.0
+    }
+}
+
+/// A HID usage.
+///
+/// The meaning of a usage depends on its [`UsagePage`], so the constants are
+/// named after the page they belong to, except for the Generic Desktop ones.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Usage(pub u16);
+
+impl Usage {
+    pub const POINTER: Usage = Usage(0x01);
+    pub const MOUSE: Usage = Usage(0x02);
+    pub const JOYSTICK: Usage = Usage(0x04);
+    pub const GAMEPAD: Usage = Usage(0x05);
+    pub const KEYBOARD: Usage = Usage(0x06);
+    pub const KEYPAD: Usage = Usage(0x07);
+    pub const MULTI_AXIS_CONTROLLER: Usage = Usage(0x08);
+    pub const SYSTEM_CONTROL: Usage = Usage(0x80);
+    pub const CONSUMER_CONTROL: Usage = Usage(0x01);
+    pub const FIDO_U2F_AUTHENTICATOR: Usage = Usage(0x01);
+}
+
+impl Display for Usage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{:#06x}", self.0)
+    }
+}
+
+impl From<u16> for Usage {
+    fn from(value: u16) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Usage> for u16 {
+    fn from(value: Usage) -> Self {
+        value.0
+    }
+}
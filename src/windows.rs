@@ -1,5 +1,9 @@
-use crate::{HidDevice, HidResult};
+#[cfg(feature = "windows-native")]
+use crate::{device_order, sort_devices, HidApiBackend};
+use crate::{DeviceInfo, HidApi, HidDevice, HidError, HidResult};
+use std::ffi::{CStr, CString};
 pub use windows_sys::core::GUID;
+use windows_sys::Win32::Foundation::HANDLE;
 
 impl HidDevice {
     /// Get the container ID for a HID device.
@@ -10,4 +14,534 @@ impl HidDevice {
     pub fn get_container_id(&self) -> HidResult<GUID> {
         self.inner.get_container_id()
     }
+
+    /// Set the size of the ring buffer, in reports, which the HID class driver
+    /// uses to queue Input reports.
+    ///
+    /// Raising it helps against lost reports on devices which send bursts of
+    /// reports. Only supported by the `windows-native` backend.
+    pub fn set_input_buffer_count(&self, count: u32) -> HidResult<()> {
+        self.inner.set_input_buffer_count(count)
+    }
+
+    /// Get the size of the ring buffer, in reports, which the HID class driver
+    /// uses to queue Input reports.
+    ///
+    /// Only supported by the `windows-native` backend.
+    pub fn get_input_buffer_count(&self) -> HidResult<u32> {
+        self.inner.get_input_buffer_count()
+    }
+
+    /// Get the physical descriptor of a HID device.
+    ///
+    /// Physical descriptors describe which part of the body is used to
+    /// activate a control, only very few devices have one. Returns
+    /// [`HidError::UnsupportedOperation`](crate::HidError::UnsupportedOperation)
+    /// if the device does not provide one. Only supported by the `windows-native` backend.
+    ///
+    /// If successful, returns the number of bytes copied into `buf`.
+    pub fn get_physical_descriptor(&self, buf: &mut [u8]) -> HidResult<usize> {
+        self.inner.get_physical_descriptor(buf)
+    }
+
+    /// Reset the device by power cycling the port of the USB hub it is plugged into.
+    ///
+    /// This recovers devices which got stuck, without unplugging them. The
+    /// device disconnects, so this handle becomes unusable and the device has
+    /// to be opened again once it is back, for example with
+    /// [`OpenHandle::reopen()`](crate::OpenHandle::reopen). Cycling a port
+    /// usually needs administrator rights.
+    ///
+    /// Returns [`HidError::UnsupportedOperation`](crate::HidError::UnsupportedOperation)
+    /// for devices which are not connected over USB. Only supported by the
+    /// `windows-native` backend.
+    pub fn request_reset(&self) -> HidResult<()> {
+        self.inner.request_reset()
+    }
+
+    /// Like [`HidDevice::get_feature_report()`], but gives up after `timeout` milliseconds.
+    ///
+    /// A `timeout` of -1 waits forever. Returns [`HidError::Timeout`](crate::HidError::Timeout)
+    /// if the device did not answer in time, in which case the request is cancelled.
+    /// Only supported by the `windows-native` backend.
+    pub fn get_feature_report_timeout(&self, buf: &mut [u8], timeout: i32) -> HidResult<usize> {
+        crate::check_report_len(self.inner.get_feature_report_timeout(buf, timeout)?, buf)
+    }
+
+    /// Get the length in bytes of the longest Input report of the device.
+    ///
+    /// This is the `InputReportByteLength` which Windows reports in the caps
+    /// of the device. It includes the Report ID byte, which is counted even
+    /// if the device does not use numbered reports, so a buffer of this size
+    /// fits every Input report. Returns an error if the preparsed data of the
+    /// device is not available. Only supported by the `windows-native` backend.
+    pub fn input_report_length(&self) -> HidResult<usize> {
+        self.inner.input_report_length()
+    }
+
+    /// Get the length in bytes of the longest Output report of the device.
+    ///
+    /// See [`HidDevice::input_report_length()`]. Only supported by the
+    /// `windows-native` backend.
+    pub fn output_report_length(&self) -> HidResult<usize> {
+        self.inner.output_report_length()
+    }
+
+    /// Get the length in bytes of the longest Feature report of the device.
+    ///
+    /// Use this to size the buffer of [`HidDevice::get_feature_report()`].
+    /// See [`HidDevice::input_report_length()`]. Only supported by the
+    /// `windows-native` backend.
+    pub fn feature_report_length(&self) -> HidResult<usize> {
+        self.inner.feature_report_length()
+    }
+
+    /// Start reading an Input report into `buf` without blocking.
+    ///
+    /// The event of the returned [`ReadOperation`] is signaled once a report
+    /// arrived, so the reads of many devices can be waited for at once, for
+    /// example with `WaitForMultipleObjects`. Afterwards, pass the operation to
+    /// [`HidDevice::finish_read()`] to get the report. Only one read can be
+    /// pending per device, so this picks up a read which is still pending from
+    /// an earlier call. Only supported by the `windows-native` backend.
+    pub fn begin_read<'a>(&self, buf: &'a mut [u8]) -> HidResult<ReadOperation<'a>> {
+        if buf.is_empty() {
+            return Err(HidError::InvalidZeroSizeData);
+        }
+        let event = self.inner.begin_read()?;
+        Ok(ReadOperation { buf, event })
+    }
+
+    /// Copy the report of a read started by [`HidDevice::begin_read()`] into its buffer.
+    ///
+    /// Does not block. If the read did not finish yet, 0 is returned and the
+    /// read stays pending, so it can be continued by calling `begin_read()` again.
+    ///
+    /// If successful, returns the number of bytes of the report.
+    pub fn finish_read(&self, op: ReadOperation<'_>) -> HidResult<usize> {
+        self.inner.finish_read(op.buf)
+    }
+}
+
+/// A read of an Input report which was started with [`HidDevice::begin_read()`].
+#[derive(Debug)]
+pub struct ReadOperation<'a> {
+    buf: &'a mut [u8],
+    event: HANDLE,
+}
+
+impl ReadOperation<'_> {
+    /// The event which is signaled when the read finishes.
+    ///
+    /// The event is owned by the device and only valid while it is open.
+    pub fn event_handle(&self) -> HANDLE {
+        self.event
+    }
+}
+
+impl DeviceInfo {
+    /// Check whether the device path refers to the same device interface as `other`.
+    ///
+    /// Windows device interface paths are case-insensitive and can be prefixed
+    /// with either `\\?\`, `\\.\` or `\??\`, so both paths are normalized before
+    /// comparing them. This normalization is specific to Windows paths, which
+    /// is why this method is only available on Windows.
+    pub fn path_eq(&self, other: &CStr) -> bool {
+        normalize_path(self.path.to_bytes()) == normalize_path(other.to_bytes())
+    }
+
+    /// A key for the device interface which stays the same across sessions.
+    ///
+    /// Windows hands out the same device interface path with different casing
+    /// and prefixes, depending on the API and the boot. The key is the device
+    /// path with these differences removed:
+    ///
+    /// 1. A leading `\\?\`, `\\.\` or `\??\` is stripped.
+    /// 2. ASCII letters are converted to lowercase. Other characters are kept.
+    ///
+    /// Bytes which are not valid UTF-8 are replaced with `U+FFFD`. Two
+    /// [`DeviceInfo`]s are only equal if their keys are equal.
+    pub fn stable_id(&self) -> String {
+        String::from_utf8_lossy(&normalize_path(self.path.to_bytes())).into_owned()
+    }
+
+    /// The container ID of the physical device this interface belongs to.
+    ///
+    /// Unlike [`HidDevice::get_container_id()`], this does not need to open the
+    /// device. Only available with the `windows-native` backend.
+    pub fn container_id(&self) -> Option<GUID> {
+        self.container_id
+    }
+
+    /// The container ID formatted with [`guid_to_string()`], for logging.
+    pub fn container_id_string(&self) -> Option<String> {
+        self.container_id.as_ref().map(guid_to_string)
+    }
+
+    /// The instance ID of the USB device this interface belongs to.
+    ///
+    /// For interfaces of composite devices, this is the instance ID of the
+    /// composite parent, so all interfaces of one physical USB device share it.
+    /// Only available for USB devices with the `windows-native` backend.
+    pub fn parent_instance_id(&self) -> Option<&str> {
+        self.parent_instance_id.as_deref()
+    }
+
+    /// The instance ID of the HID device which provides this interface, like
+    /// `HID\VID_046D&PID_C52B&MI_00\8&1B2C3D4E&0&0000`.
+    ///
+    /// This is the ID which the Device Manager and `pnputil` show for the
+    /// device. Only available with the `windows-native` backend.
+    pub fn instance_id(&self) -> Option<&str> {
+        self.instance_id.as_deref()
+    }
+}
+
+#[cfg(feature = "windows-native")]
+impl DeviceInfo {
+    /// Read the battery level of a Bluetooth LE device from its Battery Service.
+    ///
+    /// The level is in percent. Returns `None` for devices which are not
+    /// connected over Bluetooth LE or do not have a Battery Service. Each call
+    /// asks the device, so this can take a moment.
+    pub fn ble_battery_level(&self) -> HidResult<Option<u8>> {
+        if self.bus_type != crate::BusType::BluetoothLE {
+            return Ok(None);
+        }
+        HidApiBackend::get_ble_battery_level(&self.path)
+    }
+}
+
+/// Format a GUID in the canonical registry form, like
+/// `{4D1E55B2-F16F-11CF-88CB-001111000030}`.
+pub fn guid_to_string(guid: &GUID) -> String {
+    format!(
+        "{{{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+        guid.data1,
+        guid.data2,
+        guid.data3,
+        guid.data4[0],
+        guid.data4[1],
+        guid.data4[2],
+        guid.data4[3],
+        guid.data4[4],
+        guid.data4[5],
+        guid.data4[6],
+        guid.data4[7]
+    )
+}
+
+/// Compare two GUIDs, which `windows-sys` does not implement `PartialEq` for.
+pub fn guid_eq(a: &GUID, b: &GUID) -> bool {
+    guid_to_u128(a) == guid_to_u128(b)
+}
+
+fn guid_to_u128(guid: &GUID) -> u128 {
+    (guid.data1 as u128) << 96
+        | (guid.data2 as u128) << 80
+        | (guid.data3 as u128) << 64
+        | u64::from_be_bytes(guid.data4) as u128
+}
+
+const PATH_PREFIXES: [&str; 3] = [r"\\?\", r"\\.\", r"\??\"];
+
+pub(crate) fn normalize_path(path: &[u8]) -> Vec<u8> {
+    let path = PATH_PREFIXES
+        .iter()
+        .find_map(|prefix| path.strip_prefix(prefix.as_bytes()))
+        .unwrap_or(path);
+    path.to_ascii_lowercase()
+}
+
+impl HidApi {
+    /// Open a device by a device interface path from another tool, like `pnputil`.
+    ///
+    /// The path can be given with any of the `\\?\`, `\\.\` or `\??\` prefixes
+    /// or without one, and is brought into the `\\?\` form expected by
+    /// `CreateFileW`. It has to end with the interface class GUID, like
+    /// `\\?\HID#VID_046D&PID_C52B&MI_00#8&1b2c3d4e&0&0000#{4d1e55b2-f16f-11cf-88cb-001111000030}`.
+    ///
+    /// Fails with a [`HidError::IoError`] of kind [`std::io::ErrorKind::InvalidInput`]
+    /// if `path` is not a device interface path.
+    pub fn open_interface_path(&self, path: &str) -> HidResult<HidDevice> {
+        let interface_path = interface_path(path).ok_or_else(|| HidError::IoError {
+            error: std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid device interface path: {path}"),
+            ),
+        })?;
+        self.open_path(&interface_path)
+    }
+}
+
+/// Bring a device interface path into the `\\?\` form, if it looks like one
+fn interface_path(path: &str) -> Option<CString> {
+    let path = path.trim();
+    let path = PATH_PREFIXES
+        .iter()
+        .find_map(|prefix| path.strip_prefix(prefix))
+        .unwrap_or(path);
+    // The symbolic link of an interface ends with its class GUID
+    let (_, guid) = path.rsplit_once('#')?;
+    if guid.len() != 38 || !guid.starts_with('{') || !guid.ends_with('}') {
+        return None;
+    }
+    CString::new(format!(r"\\?\{path}")).ok()
+}
+
+/// Whether a device can currently be opened with read and write access.
+#[cfg(feature = "windows-native")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AccessState {
+    Available,
+    /// The device is opened exclusively by another process
+    Busy,
+}
+
+/// Statistics of an enumeration, see [`HidApi::device_list_with_stats()`].
+#[cfg(feature = "windows-native")]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EnumStats {
+    /// The number of HID interfaces which are attached
+    pub total_interfaces: usize,
+    /// The number of interfaces which were left out because they could not be opened
+    pub skipped_open_failures: usize,
+}
+
+#[cfg(feature = "windows-native")]
+impl HidApi {
+    /// Enumerate the currently attached devices like `refresh_devices()`, and
+    /// count the interfaces which were left out.
+    ///
+    /// Interfaces which can not be opened, e.g. because access to them is
+    /// denied, are missing from the device list. [`EnumStats`] tells a device
+    /// which is not connected apart from one which is not accessible. This
+    /// does not touch the internal device list.
+    pub fn device_list_with_stats(&self) -> HidResult<(Vec<DeviceInfo>, EnumStats)> {
+        let (mut devices, stats) = HidApiBackend::get_hid_device_info_vector_with_stats(0, 0)?;
+        sort_devices(&mut devices);
+        Ok((devices, stats))
+    }
+
+    /// Enumerate the currently attached devices together with their [`AccessState`].
+    ///
+    /// Every device is briefly opened with read and write access to find out
+    /// whether another process holds it exclusively. This is meant for diagnostics
+    /// and does not touch the internal device list.
+    pub fn device_list_with_access(&self) -> HidResult<Vec<(DeviceInfo, AccessState)>> {
+        let mut devices = HidApiBackend::get_hid_device_info_vector_with_access(0, 0)?;
+        devices.sort_by(|(a, _), (b, _)| device_order(a, b));
+        Ok(devices)
+    }
+
+    /// Open a device by its instance ID, like the ones shown by `pnputil`.
+    ///
+    /// `id` is compared case-insensitively against the
+    /// [`DeviceInfo::instance_id()`] of the HID interfaces and against the
+    /// [`DeviceInfo::parent_instance_id()`] of the USB device they belong to,
+    /// like `USB\VID_046D&PID_C52B\5&2A1E3C&0&2`. If the ID of a USB device
+    /// with several interfaces is given, the first interface in the order of
+    /// [`HidApi::device_list()`] is opened.
+    ///
+    /// The device is returned together with its info. Returns
+    /// [`HidError::DeviceNotFound`] if no interface matches.
+    pub fn open_by_instance_id(&self, id: &str) -> HidResult<(HidDevice, DeviceInfo)> {
+        let id = id.trim();
+        let matches =
+            |other: Option<&str>| other.is_some_and(|other| other.eq_ignore_ascii_case(id));
+        let mut devices = HidApiBackend::get_hid_device_info_vector(0, 0)?;
+        sort_devices(&mut devices);
+        let info = devices
+            .into_iter()
+            .find(|info| matches(info.instance_id()) || matches(info.parent_instance_id()))
+            .ok_or(HidError::DeviceNotFound)?;
+        let dev = self.open_path(info.path())?;
+        Ok((dev, info))
+    }
+
+    /// Enumerate all installed devices, including the ones which are currently disconnected.
+    ///
+    /// Disconnected devices can not be opened, so only their path, vendor id and
+    /// product id are known, and [`DeviceInfo::is_present()`] returns `false`
+    /// for them. This is meant for inventory tools and does not touch the
+    /// internal device list.
+    pub fn device_list_all(&self) -> HidResult<Vec<DeviceInfo>> {
+        let mut devices = HidApiBackend::get_hid_device_info_vector_all()?;
+        sort_devices(&mut devices);
+        Ok(devices)
+    }
+
+    /// Enumerate the currently attached devices, grouped by the physical device
+    /// they belong to.
+    ///
+    /// A mouse or keyboard usually has several HID interfaces, which all share
+    /// the same [`DeviceInfo::container_id()`]. Interfaces whose container ID
+    /// is unknown form a physical device of their own. The internal device list
+    /// is not touched.
+    pub fn get_physical_devices(&self) -> HidResult<Vec<PhysicalDevice>> {
+        let mut infos = HidApiBackend::get_hid_device_info_vector(0, 0)?;
+        sort_devices(&mut infos);
+        let mut devices: Vec<PhysicalDevice> = Vec::new();
+        for info in infos {
+            let id = info.container_id.as_ref().map(guid_to_u128);
+            match devices
+                .iter_mut()
+                .find(|device| id.is_some() && device.id() == id)
+            {
+                Some(device) => device.interfaces.push(info),
+                None => devices.push(PhysicalDevice {
+                    container_id: info.container_id,
+                    interfaces: vec![info],
+                }),
+            }
+        }
+        Ok(devices)
+    }
+}
+
+/// The HID interfaces of one physical device, see [`HidApi::get_physical_devices()`].
+#[cfg(feature = "windows-native")]
+#[derive(Clone)]
+pub struct PhysicalDevice {
+    container_id: Option<GUID>,
+    interfaces: Vec<DeviceInfo>,
+}
+
+#[cfg(feature = "windows-native")]
+impl PhysicalDevice {
+    fn id(&self) -> Option<u128> {
+        self.container_id.as_ref().map(guid_to_u128)
+    }
+
+    /// The container ID shared by all interfaces, if it is known
+    pub fn container_id(&self) -> Option<GUID> {
+        self.container_id
+    }
+
+    /// The vendor id of the device, taken from its first interface
+    pub fn vendor_id(&self) -> u16 {
+        self.interfaces[0].vendor_id()
+    }
+
+    /// The product id of the device, taken from its first interface
+    pub fn product_id(&self) -> u16 {
+        self.interfaces[0].product_id()
+    }
+
+    /// The HID interfaces of the device, there is always at least one
+    pub fn interfaces(&self) -> &[DeviceInfo] {
+        &self.interfaces
+    }
+}
+
+#[cfg(feature = "windows-native")]
+impl std::fmt::Debug for PhysicalDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PhysicalDevice")
+            .field("container_id", &self.id())
+            .field("interfaces", &self.interfaces)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: &str =
+        r"HID#VID_046D&PID_C52B&MI_00#8&1b2c3d4e&0&0000#{4d1e55b2-f16f-11cf-88cb-001111000030}";
+
+    #[test]
+    fn test_interface_path_prefixes() {
+        let expected = CString::new(format!(r"\\?\{PATH}")).unwrap();
+        for prefix in ["", r"\\?\", r"\\.\", r"\??\"] {
+            assert_eq!(
+                Some(&*expected),
+                interface_path(&format!("{prefix}{PATH}")).as_deref()
+            );
+        }
+    }
+
+    #[test]
+    fn test_interface_path_round_trip() {
+        let path = interface_path(PATH).unwrap();
+        assert_eq!(
+            Some(&*path),
+            interface_path(path.to_str().unwrap()).as_deref()
+        );
+    }
+
+    #[test]
+    #[ignore = "needs a HID device which can be opened"]
+    fn test_open_interface_path_from_enumeration() {
+        let api = HidApi::new().unwrap();
+        let Some(info) = api
+            .device_list()
+            .find(|info| api.open_path(info.path()).is_ok())
+        else {
+            return;
+        };
+        // Other tools usually print the path without the prefix
+        let path = info.path().to_str().unwrap();
+        let path = path.strip_prefix(r"\\?\").unwrap_or(path);
+
+        let device = api.open_interface_path(path).unwrap();
+        let opened = device.get_device_info().unwrap();
+        assert_eq!(
+            normalize_path(info.path().to_bytes()),
+            normalize_path(opened.path().to_bytes())
+        );
+    }
+
+    #[test]
+    fn test_open_interface_path_invalid() {
+        let api = HidApi::builder().enumerate(false).build().unwrap();
+        match api.open_interface_path("COM1") {
+            Err(HidError::IoError { error }) => {
+                assert_eq!(std::io::ErrorKind::InvalidInput, error.kind())
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_interface_path_without_guid() {
+        assert_eq!(None, interface_path(r"\\?\HID#VID_046D&PID_C52B"));
+        assert_eq!(None, interface_path(r"HID#VID_046D&PID_C52B#{4d1e55b2}"));
+        assert_eq!(None, interface_path("COM1"));
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        let expected = PATH.to_ascii_lowercase().into_bytes();
+        for prefix in ["", r"\\?\", r"\\.\", r"\??\"] {
+            let path = format!("{prefix}{PATH}");
+            assert_eq!(expected, normalize_path(path.as_bytes()));
+            assert_eq!(
+                expected,
+                normalize_path(path.to_ascii_uppercase().as_bytes())
+            );
+        }
+    }
+
+    #[test]
+    fn test_guid_to_u128() {
+        let id = 0x4d1e55b2_f16f_11cf_88cb_001111000030;
+        assert_eq!(id, guid_to_u128(&GUID::from_u128(id)));
+    }
+
+    #[test]
+    fn test_guid_to_string() {
+        let guid = GUID::from_u128(0x4d1e55b2_f16f_11cf_88cb_001111000030);
+        assert_eq!(
+            "{4D1E55B2-F16F-11CF-88CB-001111000030}",
+            guid_to_string(&guid)
+        );
+        assert!(guid_eq(
+            &guid,
+            &GUID::from_u128(0x4d1e55b2_f16f_11cf_88cb_001111000030)
+        ));
+        assert!(!guid_eq(&guid, &GUID::from_u128(0)));
+    }
 }
@@ -0,0 +1,142 @@
+use crate::windows_native::dev_node::DevNode;
+use crate::windows_native::device_info::get_bus;
+use crate::windows_native::error::{Win32Error, WinError, WinResult};
+use crate::windows_native::interfaces::Interface;
+use crate::windows_native::string::{U16Str, U16String};
+use crate::windows_native::types::{Handle, InternalBusType};
+use std::mem::zeroed;
+use std::ptr::{null, null_mut};
+use windows_sys::core::{GUID, HRESULT};
+use windows_sys::Win32::Devices::Bluetooth::{
+    BluetoothGATTGetCharacteristicValue, BluetoothGATTGetCharacteristics, BluetoothGATTGetServices,
+    BLUETOOTH_GATT_FLAG_NONE, BTH_LE_GATT_CHARACTERISTIC, BTH_LE_GATT_CHARACTERISTIC_VALUE,
+    BTH_LE_GATT_SERVICE,
+};
+use windows_sys::Win32::Devices::Properties::DEVPKEY_Device_InstanceId;
+use windows_sys::Win32::Foundation::{
+    ERROR_MORE_DATA, GENERIC_READ, GENERIC_WRITE, INVALID_HANDLE_VALUE, S_OK,
+};
+use windows_sys::Win32::Storage::FileSystem::{
+    CreateFileW, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+};
+
+/// The Battery Service of the Bluetooth SIG. Windows uses the service UUID as the
+/// class of the device interface it creates for the service.
+const BATTERY_SERVICE: GUID = GUID::from_u128(0x0000180f_0000_1000_8000_00805f9b34fb);
+/// The Battery Level characteristic of the Battery Service
+const BATTERY_LEVEL: u16 = 0x2a19;
+
+fn check_hresult(hr: HRESULT) -> WinResult<()> {
+    match hr {
+        S_OK => Ok(()),
+        // HRESULT_FROM_WIN32 keeps the Win32 error code in the lower 16 bits
+        hr => Err(Win32Error::Generic((hr & 0xffff) as u32).into()),
+    }
+}
+
+fn is_more_data(hr: HRESULT) -> bool {
+    hr & 0xffff == ERROR_MORE_DATA as HRESULT
+}
+
+/// Read the battery level of the Bluetooth LE device the HID interface `path` belongs to.
+///
+/// Returns `None` for devices which are not connected over Bluetooth LE or which
+/// do not have a Battery Service.
+pub fn battery_level(path: &U16Str) -> WinResult<Option<u8>> {
+    let (bus_type, hid_service) = get_bus(path)?;
+    if bus_type != InternalBusType::BluetoothLE {
+        return Ok(None);
+    }
+    // The GATT services of a device, including HID over GATT, are siblings below the device
+    let device = hid_service.parent()?;
+
+    for interface in Interface::get_interface_list_of(BATTERY_SERVICE, None)?.iter() {
+        let service_id: U16String = Interface::get_property(interface, DEVPKEY_Device_InstanceId)?;
+        if DevNode::from_device_id(&service_id)?.parent()? == device {
+            return read_battery_level(interface);
+        }
+    }
+    Ok(None)
+}
+
+fn read_battery_level(interface: &U16Str) -> WinResult<Option<u8>> {
+    // The GATT functions are synchronous, so the service is opened without FILE_FLAG_OVERLAPPED
+    let handle = unsafe {
+        CreateFileW(
+            interface.as_ptr(),
+            GENERIC_READ | GENERIC_WRITE,
+            FILE_SHARE_READ | FILE_SHARE_WRITE,
+            null(),
+            OPEN_EXISTING,
+            0,
+            0,
+        )
+    };
+    ensure!(handle != INVALID_HANDLE_VALUE, Err(WinError::last()));
+    let handle = Handle::from_raw(handle);
+
+    let mut service: BTH_LE_GATT_SERVICE = unsafe { zeroed() };
+    let mut count = 0;
+    // The handle of a service interface only lists the service itself
+    check_hresult(unsafe {
+        BluetoothGATTGetServices(
+            handle.as_raw(),
+            1,
+            &mut service,
+            &mut count,
+            BLUETOOTH_GATT_FLAG_NONE,
+        )
+    })?;
+
+    let hr = unsafe {
+        BluetoothGATTGetCharacteristics(
+            handle.as_raw(),
+            &service,
+            0,
+            null_mut(),
+            &mut count,
+            BLUETOOTH_GATT_FLAG_NONE,
+        )
+    };
+    // Asking for the number of characteristics fails with ERROR_MORE_DATA
+    if !is_more_data(hr) {
+        check_hresult(hr)?;
+    }
+    if count == 0 {
+        return Ok(None);
+    }
+    let mut characteristics: Vec<BTH_LE_GATT_CHARACTERISTIC> =
+        vec![unsafe { zeroed() }; count as usize];
+    check_hresult(unsafe {
+        BluetoothGATTGetCharacteristics(
+            handle.as_raw(),
+            &service,
+            count,
+            characteristics.as_mut_ptr(),
+            &mut count,
+            BLUETOOTH_GATT_FLAG_NONE,
+        )
+    })?;
+    let Some(characteristic) = characteristics.iter().take(count as usize).find(|c| {
+        c.CharacteristicUuid.IsShortUuid != 0
+            && unsafe { c.CharacteristicUuid.Value.ShortUuid } == BATTERY_LEVEL
+    }) else {
+        return Ok(None);
+    };
+
+    // The value is a single byte, which fits into the `Data` array of the struct itself
+    let mut value: BTH_LE_GATT_CHARACTERISTIC_VALUE = unsafe { zeroed() };
+    let mut required = 0;
+    check_hresult(unsafe {
+        BluetoothGATTGetCharacteristicValue(
+            handle.as_raw(),
+            characteristic,
+            std::mem::size_of::<BTH_LE_GATT_CHARACTERISTIC_VALUE>() as u32,
+            &mut value,
+            &mut required,
+            BLUETOOTH_GATT_FLAG_NONE,
+        )
+    })?;
+    ensure!(value.DataSize >= 1, Err(WinError::UnexpectedReturnSize));
+    Ok(Some(value.Data[0]))
+}
@@ -1,18 +1,20 @@
 use crate::windows_native::dev_node::DevNode;
-use crate::windows_native::error::WinResult;
+use crate::windows_native::error::{WinError, WinResult};
 use crate::windows_native::hid::{get_hid_attributes, PreparsedData};
+use crate::windows_native::instance_id::UsbInstanceId;
 use crate::windows_native::interfaces::Interface;
 use crate::windows_native::string::{U16Str, U16String, U16StringList};
 use crate::windows_native::types::{Handle, InternalBusType};
-use crate::{BusType, DeviceInfo, WcharString};
+use crate::{enumeration_warning, BusType, DeviceInfo, EnumWarning, WcharString};
 use std::ffi::{c_void, CString};
-use std::mem::{size_of, zeroed};
+use std::mem::size_of;
 use windows_sys::Win32::Devices::HumanInterfaceDevice::{
     HidD_GetManufacturerString, HidD_GetProductString, HidD_GetSerialNumberString,
 };
 use windows_sys::Win32::Devices::Properties::{
-    DEVPKEY_Device_CompatibleIds, DEVPKEY_Device_HardwareIds, DEVPKEY_Device_InstanceId,
-    DEVPKEY_Device_Manufacturer, DEVPKEY_NAME,
+    DEVPKEY_Device_BusReportedDeviceDesc, DEVPKEY_Device_CompatibleIds, DEVPKEY_Device_ContainerId,
+    DEVPKEY_Device_HardwareIds, DEVPKEY_Device_InstanceId, DEVPKEY_Device_Manufacturer,
+    DEVPKEY_NAME,
 };
 use windows_sys::Win32::Foundation::{BOOLEAN, HANDLE};
 use windows_sys::Win32::Storage::EnhancedStorage::{
@@ -25,6 +27,14 @@ fn read_string(
     handle: &Handle,
 ) -> WcharString {
     // Return empty string on failure to match the c implementation
+    try_read_string(func, handle).unwrap_or_else(|| WcharString::String(String::new()))
+}
+
+/// Like [`read_string`], but returns `None` if the query itself failed
+fn try_read_string(
+    func: unsafe extern "system" fn(HANDLE, *mut c_void, u32) -> BOOLEAN,
+    handle: &Handle,
+) -> Option<WcharString> {
     let mut string = [0u16; 256];
     if unsafe {
         func(
@@ -34,41 +44,133 @@ fn read_string(
         )
     } != 0
     {
-        U16Str::from_slice_list(&string)
-            .map(WcharString::from)
-            .next()
-            .unwrap_or_else(|| WcharString::String(String::new()))
+        Some(
+            U16Str::from_slice_list(&string)
+                .map(WcharString::from)
+                .next()
+                .unwrap_or_else(|| WcharString::String(String::new())),
+        )
     } else {
-        // WcharString::None
-        WcharString::String(String::new())
+        None
     }
 }
 
-pub fn get_device_info(path: &U16Str, handle: &Handle) -> DeviceInfo {
+pub fn get_device_info(path: &U16Str, handle: &Handle) -> WinResult<DeviceInfo> {
+    get_device_info_on_bus(path, handle, get_bus(path))
+}
+
+/// Like [`get_device_info`], but reuses the result of [`get_bus`] if it is already known
+pub fn get_device_info_on_bus(
+    path: &U16Str,
+    handle: &Handle,
+    bus: WinResult<(InternalBusType, DevNode)>,
+) -> WinResult<DeviceInfo> {
+    // Interface paths are ASCII in practice, so a lossy conversion never changes a valid path
+    let c_path = CString::new(String::from_utf16_lossy(path.as_slice()))
+        .map_err(|_| WinError::InvalidDevicePath)?;
     let attrib = get_hid_attributes(handle);
+    let serial_number = try_read_string(HidD_GetSerialNumberString, handle);
+    let serial_number_failed = serial_number.is_none();
     let caps = PreparsedData::load(handle)
         .and_then(|data| data.get_caps())
-        .unwrap_or(unsafe { zeroed() });
+        .ok();
     let mut dev = DeviceInfo {
-        path: CString::new(path.to_string()).unwrap(),
+        path: c_path,
         vendor_id: attrib.VendorID,
         product_id: attrib.ProductID,
-        serial_number: read_string(HidD_GetSerialNumberString, handle),
+        serial_number: serial_number.unwrap_or_else(|| WcharString::String(String::new())),
         release_number: attrib.VersionNumber,
         manufacturer_string: read_string(HidD_GetManufacturerString, handle),
         product_string: read_string(HidD_GetProductString, handle),
-        usage_page: caps.UsagePage,
-        usage: caps.Usage,
+        usage_page: caps.map_or(0, |caps| caps.UsagePage),
+        usage: caps.map_or(0, |caps| caps.Usage),
+        usage_known: caps.is_some(),
         interface_number: -1,
         bus_type: BusType::Unknown,
+        input_report_length: caps.map(|caps| caps.InputReportByteLength),
+        output_report_length: caps.map(|caps| caps.OutputReportByteLength),
+        feature_report_length: caps.map(|caps| caps.FeatureReportByteLength),
+        usb_bcd_device: None,
+        present: true,
+        friendly_name: None,
+        container_id: None,
+        parent_instance_id: None,
+        instance_id: get_instance_id(path),
     };
 
-    // If this fails just ignore it. The data might be incomplete but at least there is something
-    let _ = get_internal_info(path, &mut dev);
-    dev
+    // If this fails just report it. The data might be incomplete but at least there is something
+    match bus {
+        Err(_) => enumeration_warning(&dev.path, EnumWarning::BusTypeUnknown),
+        // Virtual and other non-USB/Bluetooth/I2C/SPI devices have nothing more to query
+        Ok((InternalBusType::Unknown, _)) => {}
+        Ok((bus_type, dev_node)) => {
+            if get_internal_info(&mut dev, bus_type, dev_node).is_err() {
+                enumeration_warning(&dev.path, EnumWarning::BusInfoFailed);
+            }
+        }
+    }
+    // Only a problem if none of the fallbacks found a serial number either
+    if serial_number_failed && dev.serial_number().is_none_or(str::is_empty) {
+        enumeration_warning(&dev.path, EnumWarning::SerialFetchFailed);
+    }
+    Ok(dev)
 }
 
-fn get_internal_info(interface_path: &U16Str, dev: &mut DeviceInfo) -> WinResult<()> {
+/// Get the little information that is available without opening the device.
+///
+/// The vendor and product id are taken from the interface path, which contains
+/// them for USB and Bluetooth devices.
+pub fn get_minimal_device_info(interface_path: &U16Str, present: bool) -> WinResult<DeviceInfo> {
+    let path = String::from_utf16_lossy(interface_path.as_slice());
+    let mut vendor_id = 0;
+    let mut product_id = 0;
+    for token in path.split(['#', '&']) {
+        if let Some((name, value)) = token.split_once('_') {
+            match name.to_ascii_uppercase().as_str() {
+                "VID" => vendor_id = u16::from_str_radix(value, 16).unwrap_or(0),
+                "PID" => product_id = u16::from_str_radix(value, 16).unwrap_or(0),
+                _ => (),
+            }
+        }
+    }
+    let instance_id = get_instance_id(interface_path);
+    Ok(DeviceInfo {
+        path: CString::new(path).map_err(|_| WinError::InvalidDevicePath)?,
+        vendor_id,
+        product_id,
+        serial_number: WcharString::None,
+        release_number: 0,
+        manufacturer_string: WcharString::None,
+        product_string: WcharString::None,
+        usage_page: 0,
+        usage: 0,
+        usage_known: false,
+        interface_number: -1,
+        bus_type: BusType::Unknown,
+        input_report_length: None,
+        output_report_length: None,
+        feature_report_length: None,
+        usb_bcd_device: None,
+        present,
+        friendly_name: None,
+        container_id: None,
+        parent_instance_id: None,
+        instance_id,
+    })
+}
+
+/// The instance ID of the HID device node which provides the interface
+fn get_instance_id(interface_path: &U16Str) -> Option<String> {
+    Interface::get_property::<U16String>(interface_path, DEVPKEY_Device_InstanceId)
+        .ok()
+        .map(|id| String::from_utf16_lossy(id.as_slice()))
+}
+
+/// Classify the bus of a device interface by the compatible IDs of its parent dev node.
+///
+/// This only walks the dev node tree and does not need to open the device, so it
+/// is cheap enough to filter devices before gathering the rest of their information.
+pub fn get_bus(interface_path: &U16Str) -> WinResult<(InternalBusType, DevNode)> {
     let device_id: U16String = Interface::get_property(interface_path, DEVPKEY_Device_InstanceId)?;
 
     let dev_node = DevNode::from_device_id(&device_id)?.parent()?;
@@ -98,10 +200,25 @@ fn get_internal_info(interface_path: &U16Str, dev: &mut DeviceInfo) -> WinResult
         })
         .next()
         .unwrap_or(InternalBusType::Unknown);
+    Ok((bus_type, dev_node))
+}
+
+fn get_internal_info(
+    dev: &mut DeviceInfo,
+    bus_type: InternalBusType,
+    dev_node: DevNode,
+) -> WinResult<()> {
     dev.bus_type = bus_type.into();
+    dev.container_id = dev_node.get_property(DEVPKEY_Device_ContainerId).ok();
+    dev.friendly_name = dev_node
+        .get_property::<U16String>(DEVPKEY_NAME)
+        .ok()
+        .map(|name| String::from_utf16_lossy(name.as_slice()));
     match bus_type {
         InternalBusType::Usb => get_usb_info(dev, dev_node)?,
         InternalBusType::BluetoothLE => get_ble_info(dev, dev_node)?,
+        InternalBusType::I2c => get_i2c_info(dev, dev_node)?,
+        InternalBusType::Spi => get_spi_info(dev, dev_node)?,
         _ => (),
     };
 
@@ -109,34 +226,37 @@ fn get_internal_info(interface_path: &U16Str, dev: &mut DeviceInfo) -> WinResult
 }
 
 fn get_usb_info(dev: &mut DeviceInfo, mut dev_node: DevNode) -> WinResult<()> {
-    let mut device_id: U16String = dev_node.get_property(DEVPKEY_Device_InstanceId)?;
+    let device_id: U16String = dev_node.get_property(DEVPKEY_Device_InstanceId)?;
 
-    device_id.make_uppercase_ascii();
     // Check for Xbox Common Controller class (XUSB) device.
     // https://docs.microsoft.com/windows/win32/xinput/directinput-and-xusb-devices
     // https://docs.microsoft.com/windows/win32/xinput/xinput-and-directinput
     //
-    if extract_int_token_value(&device_id, "IG_").is_some() {
+    if UsbInstanceId::parse(&String::from_utf16_lossy(device_id.as_slice()))
+        .is_some_and(|id| id.ig.is_some())
+    {
         dev_node = dev_node.parent()?;
     }
 
-    let mut hardware_ids: U16StringList = dev_node.get_property(DEVPKEY_Device_HardwareIds)?;
+    let hardware_ids: U16StringList = dev_node.get_property(DEVPKEY_Device_HardwareIds)?;
 
     // Get additional information from USB device's Hardware ID
     // https://docs.microsoft.com/windows-hardware/drivers/install/standard-usb-identifiers
     // https://docs.microsoft.com/windows-hardware/drivers/usbcon/enumeration-of-interfaces-not-grouped-in-collections
     //
-    for hardware_id in hardware_ids.iter_mut() {
-        hardware_id.make_uppercase_ascii();
-
-        if dev.release_number == 0 {
-            if let Some(release_number) = extract_int_token_value(hardware_id, "REV_") {
-                dev.release_number = release_number as u16;
+    for hardware_id in hardware_ids.iter().filter_map(|hardware_id| {
+        UsbInstanceId::parse(&String::from_utf16_lossy(hardware_id.as_slice()))
+    }) {
+        if let Some(rev) = hardware_id.rev {
+            dev.usb_bcd_device.get_or_insert(rev);
+            // Keep the version of the HID attributes, unless it is missing
+            if dev.release_number == 0 {
+                dev.release_number = rev;
             }
         }
         if dev.interface_number == -1 {
-            if let Some(interface_number) = extract_int_token_value(hardware_id, "MI_") {
-                dev.interface_number = interface_number as i32;
+            if let Some(mi) = hardware_id.mi {
+                dev.interface_number = mi.into();
             }
         }
     }
@@ -150,28 +270,41 @@ fn get_usb_info(dev: &mut DeviceInfo, mut dev_node: DevNode) -> WinResult<()> {
         }
     }
 
+    // The USB device this interface belongs to. For composite devices this is the
+    // composite parent, which is the parent of the dev node of the interface.
+    // https://docs.microsoft.com/windows-hardware/drivers/usbcon/enumeration-of-the-composite-parent-device
+    let usb_dev_node = if dev.interface_number != -1 {
+        dev_node.parent().ok()
+    } else {
+        Some(dev_node)
+    };
+    let usb_device_id = usb_dev_node
+        .and_then(|node| {
+            node.get_property::<U16String>(DEVPKEY_Device_InstanceId)
+                .ok()
+        })
+        .map(|id| String::from_utf16_lossy(id.as_slice()));
+
     // Try to get USB device serial number if not provided by HidD_GetSerialNumberString.
     if dev.serial_number().map_or(true, str::is_empty) {
-        let mut usb_dev_node = dev_node;
-        if dev.interface_number != -1 {
-            // Get devnode parent to reach out composite parent USB device.
-            // https://docs.microsoft.com/windows-hardware/drivers/usbcon/enumeration-of-the-composite-parent-device
-            usb_dev_node = dev_node.parent()?;
+        // For USB devices the last part of the Instance ID may contain the device's serial number.
+        if let Some(serial) = usb_device_id
+            .as_deref()
+            .and_then(UsbInstanceId::parse)
+            .and_then(|id| id.serial)
+        {
+            dev.serial_number = WcharString::String(serial);
         }
+    }
+    dev.parent_instance_id = usb_device_id;
 
-        let device_id: U16String = usb_dev_node.get_property(DEVPKEY_Device_InstanceId)?;
-
-        // Extract substring after last '\\' of Instance ID.
-        // For USB devices it may contain device's serial number.
-        // https://docs.microsoft.com/windows-hardware/drivers/install/instance-ids
-        //
-        if let Some(start) = device_id
-            .as_slice()
-            .rsplit(|c| *c != b'&' as u16)
-            .next()
-            .and_then(|s| s.iter().rposition(|c| *c != b'\\' as u16))
+    // Try the description the device reported to the bus, which is the iProduct
+    // string descriptor, if HidD_GetProductString did not provide the product string.
+    if dev.product_string().map_or(true, str::is_empty) {
+        if let Ok(product_string) =
+            dev_node.get_property::<U16String>(DEVPKEY_Device_BusReportedDeviceDesc)
         {
-            dev.serial_number = U16Str::from_slice(&device_id.as_slice()[(start + 1)..]).into();
+            dev.product_string = product_string.into();
         }
     }
 
@@ -219,9 +352,27 @@ fn get_ble_info(dev: &mut DeviceInfo, dev_node: DevNode) -> WinResult<()> {
     Ok(())
 }
 
-fn extract_int_token_value(u16str: &U16Str, token: &str) -> Option<u32> {
-    let start = u16str.find_index(token)? + token.encode_utf16().count();
-    char::decode_utf16(u16str.as_slice()[start..].iter().copied())
-        .map_while(|c| c.ok().and_then(|c| c.to_digit(16)))
-        .reduce(|l, r| l * 16 + r)
+// HID over I2C devices are enumerated through ACPI, so the HidD string queries often come back empty.
+// Fall back to the strings of the ACPI dev node instead.
+fn get_i2c_info(dev: &mut DeviceInfo, dev_node: DevNode) -> WinResult<()> {
+    if dev.manufacturer_string().map_or(true, str::is_empty) {
+        if let Ok(manufacturer_string) =
+            dev_node.get_property::<U16String>(DEVPKEY_Device_Manufacturer)
+        {
+            dev.manufacturer_string = manufacturer_string.into();
+        }
+    }
+
+    if dev.product_string().map_or(true, str::is_empty) {
+        if let Ok(product_string) = dev_node.get_property::<U16String>(DEVPKEY_NAME) {
+            dev.product_string = product_string.into();
+        }
+    }
+
+    Ok(())
+}
+
+// HID over SPI devices are enumerated through ACPI just like the I2C ones
+fn get_spi_info(dev: &mut DeviceInfo, dev_node: DevNode) -> WinResult<()> {
+    get_i2c_info(dev, dev_node)
 }
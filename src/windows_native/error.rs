@@ -1,4 +1,4 @@
-use crate::HidError;
+use crate::{HidError, HidResult};
 use windows_sys::Win32::Devices::DeviceAndDriverInstallation::*;
 use windows_sys::Win32::Foundation::*;
 
@@ -15,6 +15,7 @@ pub enum WinError {
     WrongPropertyDataType,
     UnexpectedReturnSize,
     InvalidPreparsedData,
+    InvalidDevicePath,
     WaitTimedOut,
 }
 
@@ -22,11 +23,52 @@ impl WinError {
     pub fn last() -> Self {
         Self::from(Win32Error::last())
     }
+
+    /// The error of a pending read or write, which also fails with
+    /// `ERROR_GEN_FAILURE` if the device was unplugged.
+    ///
+    /// Other requests, like feature reports, fail with the same code if the
+    /// device stalls while it is still plugged in, so this is not done for them.
+    pub fn into_transfer_error(self) -> Self {
+        match self {
+            WinError::Win32(Win32Error::Generic(ERROR_GEN_FAILURE)) => {
+                WinError::Win32(Win32Error::DeviceNotConnected)
+            }
+            err => err,
+        }
+    }
+
+    /// The error of the OS, if this error carries a code of the OS
+    fn os_error(&self) -> Option<std::io::Error> {
+        let code = match *self {
+            WinError::Win32(Win32Error::Generic(code)) => code,
+            WinError::Config(ret) => unsafe { CM_MapCrToWin32Err(ret, ERROR_GEN_FAILURE) },
+            _ => return None,
+        };
+        Some(std::io::Error::from_raw_os_error(code as _))
+    }
+}
+
+/// Turn the errors of the OS into a [`HidError::Backend`], which keeps the OS error as its source
+pub trait ResultExt<T> {
+    /// `context` describes what failed, usually by the name of the OS function
+    fn context(self, context: &'static str) -> HidResult<T>;
+}
+
+impl<T> ResultExt<T> for WinResult<T> {
+    fn context(self, context: &'static str) -> HidResult<T> {
+        self.map_err(|err| match err.os_error() {
+            Some(error) => HidError::Backend { context, error },
+            None => err.into(),
+        })
+    }
 }
 
 impl From<WinError> for HidError {
     fn from(value: WinError) -> Self {
         match value {
+            WinError::Win32(Win32Error::DeviceNotConnected) => HidError::Disconnected,
+            WinError::Win32(Win32Error::OperationAborted) => HidError::Cancelled,
             WinError::Win32(Win32Error::Generic(err)) => HidError::IoError {
                 error: std::io::Error::from_raw_os_error(err as _),
             },
@@ -69,6 +111,8 @@ pub enum Win32Error {
     Success,
     IoPending,
     WaitTimedOut,
+    DeviceNotConnected,
+    OperationAborted,
 }
 
 impl Win32Error {
@@ -77,6 +121,9 @@ impl Win32Error {
             NO_ERROR => Self::Success,
             ERROR_IO_PENDING => Self::IoPending,
             ERROR_IO_INCOMPLETE | WAIT_TIMEOUT => Self::WaitTimedOut,
+            ERROR_DEVICE_NOT_CONNECTED => Self::DeviceNotConnected,
+            // Transfers which were cancelled with `CancelIoEx`
+            ERROR_OPERATION_ABORTED => Self::OperationAborted,
             code => Self::Generic(code),
         }
     }
@@ -27,6 +27,18 @@ pub fn get_hid_attributes(handle: &Handle) -> HIDD_ATTRIBUTES {
     }
 }
 
+/// Like [`get_hid_attributes`], but fails if the driver does not answer
+pub fn try_get_hid_attributes(handle: &Handle) -> WinResult<HIDD_ATTRIBUTES> {
+    unsafe {
+        let mut attrib = HIDD_ATTRIBUTES {
+            Size: size_of::<HIDD_ATTRIBUTES>() as u32,
+            ..zeroed()
+        };
+        check_boolean(HidD_GetAttributes(handle.as_raw(), &mut attrib))?;
+        Ok(attrib)
+    }
+}
+
 #[repr(transparent)]
 pub struct PreparsedData(isize);
 
@@ -0,0 +1,103 @@
+/// The components of a USB device instance ID or hardware ID.
+///
+/// USB instance IDs look like `USB\VID_046D&PID_C52B\5&2A1E3C&0&2` or
+/// `USB\VID_1050&PID_0407&MI_01\7&1C3B8F9&0&0001`, and hardware IDs like
+/// `USB\VID_046D&PID_C52B&REV_1201`. See the
+/// [standard USB identifiers](https://docs.microsoft.com/windows-hardware/drivers/install/standard-usb-identifiers).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsbInstanceId {
+    pub vid: u16,
+    pub pid: u16,
+    /// The revision, only part of hardware IDs
+    pub rev: Option<u16>,
+    /// The interface number of a composite device
+    pub mi: Option<u8>,
+    /// The interface of an Xbox Common Controller class (XUSB) device
+    pub ig: Option<u8>,
+    /// The serial number, if the device provides one
+    pub serial: Option<String>,
+}
+
+impl UsbInstanceId {
+    /// Parse an instance ID or hardware ID of the `USB` enumerator.
+    ///
+    /// Returns `None` if the ID does not belong to a USB device or does not
+    /// contain the vendor and product id. Matching is case-insensitive.
+    pub fn parse(id: &str) -> Option<UsbInstanceId> {
+        let mut parts = id.split('\\');
+        if !parts.next()?.eq_ignore_ascii_case("USB") {
+            return None;
+        }
+
+        let mut vid = None;
+        let mut pid = None;
+        let mut rev = None;
+        let mut mi = None;
+        let mut ig = None;
+        for token in parts.next()?.split('&') {
+            let (name, value) = token.split_once('_')?;
+            match name.to_ascii_uppercase().as_str() {
+                "VID" => vid = Some(u16::from_str_radix(value, 16).ok()?),
+                "PID" => pid = Some(u16::from_str_radix(value, 16).ok()?),
+                "REV" => rev = Some(u16::from_str_radix(value, 16).ok()?),
+                "MI" => mi = Some(u8::from_str_radix(value, 16).ok()?),
+                "IG" => ig = Some(u8::from_str_radix(value, 16).ok()?),
+                _ => (),
+            }
+        }
+
+        // Instance IDs are only unique within the scope of the bus if they contain
+        // a '&', which means that the device has no serial number.
+        // https://docs.microsoft.com/windows-hardware/drivers/install/instance-ids
+        let serial = parts
+            .next()
+            .filter(|serial| !serial.is_empty() && !serial.contains('&'))
+            .map(String::from);
+
+        Some(UsbInstanceId {
+            vid: vid?,
+            pid: pid?,
+            rev,
+            mi,
+            ig,
+            serial,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_instance_id_with_serial() {
+        let id = UsbInstanceId::parse(r"USB\VID_1050&PID_0407\0123456789").unwrap();
+        assert_eq!(0x1050, id.vid);
+        assert_eq!(0x0407, id.pid);
+        assert_eq!(None, id.mi);
+        assert_eq!(Some("0123456789"), id.serial.as_deref());
+    }
+
+    #[test]
+    fn test_parse_instance_id_without_serial() {
+        let id = UsbInstanceId::parse(r"usb\vid_1050&pid_0407&mi_01\7&1c3b8f9&0&0001").unwrap();
+        assert_eq!(Some(1), id.mi);
+        assert_eq!(None, id.serial);
+    }
+
+    #[test]
+    fn test_parse_hardware_id() {
+        let id = UsbInstanceId::parse(r"USB\VID_045E&PID_028E&REV_0114&IG_00").unwrap();
+        assert_eq!(Some(0x0114), id.rev);
+        assert_eq!(Some(0), id.ig);
+        assert_eq!(None, id.serial);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(None, UsbInstanceId::parse(r"HID\VID_1050&PID_0407\1"));
+        assert_eq!(None, UsbInstanceId::parse(r"USB\ROOT_HUB30\4&1"));
+        assert_eq!(None, UsbInstanceId::parse(r"USB\VID_XYZ&PID_0407"));
+        assert_eq!(None, UsbInstanceId::parse("USB"));
+    }
+}
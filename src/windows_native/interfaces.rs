@@ -6,10 +6,14 @@ use std::ptr::{null, null_mut};
 use windows_sys::core::GUID;
 use windows_sys::Win32::Devices::DeviceAndDriverInstallation::{
     CM_Get_Device_Interface_ListW, CM_Get_Device_Interface_List_SizeW,
-    CM_Get_Device_Interface_PropertyW, CM_GET_DEVICE_INTERFACE_LIST_PRESENT, CR_BUFFER_SMALL,
-    CR_SUCCESS,
+    CM_Get_Device_Interface_PropertyW, CM_GET_DEVICE_INTERFACE_LIST_ALL_DEVICES,
+    CM_GET_DEVICE_INTERFACE_LIST_PRESENT, CR_BUFFER_SMALL, CR_SUCCESS,
 };
 
+/// How often the interface list is fetched again when it grew in between,
+/// before giving up. Devices are plugged in far slower than this in practice.
+const MAX_INTERFACE_LIST_ATTEMPTS: usize = 8;
+
 pub struct Interface;
 
 impl Interface {
@@ -61,33 +65,71 @@ impl Interface {
         Ok(property)
     }
 
-    fn get_interface_list_length(interface: GUID) -> WinResult<usize> {
+    fn get_interface_list_length(
+        interface: GUID,
+        device_id: Option<&U16Str>,
+        flags: u32,
+    ) -> WinResult<usize> {
         let mut len = 0;
         let cr = unsafe {
             CM_Get_Device_Interface_List_SizeW(
                 &mut len,
                 &interface,
-                null(),
-                CM_GET_DEVICE_INTERFACE_LIST_PRESENT,
+                device_id.map_or(null(), U16Str::as_ptr),
+                flags,
             )
         };
         check_config(cr, CR_SUCCESS)?;
         Ok(len as usize)
     }
 
+    /// Get the paths of all present HID interfaces
     pub fn get_interface_list() -> WinResult<U16StringList> {
-        let interface_class_guid = get_interface_guid();
+        Self::get_interface_list_of(get_interface_guid(), None)
+    }
+
+    /// Get the paths of all installed HID interfaces, including the ones which are not present
+    pub fn get_all_interface_list() -> WinResult<U16StringList> {
+        Self::get_interface_list_with_flags(
+            get_interface_guid(),
+            None,
+            CM_GET_DEVICE_INTERFACE_LIST_ALL_DEVICES,
+        )
+    }
+
+    /// Get the paths of the present interfaces of the given class,
+    /// optionally only the ones of the device with the given instance ID
+    pub fn get_interface_list_of(
+        interface_class_guid: GUID,
+        device_id: Option<&U16Str>,
+    ) -> WinResult<U16StringList> {
+        Self::get_interface_list_with_flags(
+            interface_class_guid,
+            device_id,
+            CM_GET_DEVICE_INTERFACE_LIST_PRESENT,
+        )
+    }
 
+    fn get_interface_list_with_flags(
+        interface_class_guid: GUID,
+        device_id: Option<&U16Str>,
+        flags: u32,
+    ) -> WinResult<U16StringList> {
         let mut device_interface_list = Vec::new();
-        loop {
-            device_interface_list.resize(Self::get_interface_list_length(interface_class_guid)?, 0);
+        // Devices can be added between querying the size and fetching the list,
+        // so the list might not fit into the buffer anymore
+        for _ in 0..MAX_INTERFACE_LIST_ATTEMPTS {
+            device_interface_list.resize(
+                Self::get_interface_list_length(interface_class_guid, device_id, flags)?,
+                0,
+            );
             let cr = unsafe {
                 CM_Get_Device_Interface_ListW(
                     &interface_class_guid,
-                    null(),
+                    device_id.map_or(null(), U16Str::as_ptr),
                     device_interface_list.as_mut_ptr(),
                     device_interface_list.len() as u32,
-                    CM_GET_DEVICE_INTERFACE_LIST_PRESENT,
+                    flags,
                 )
             };
             if cr == CR_SUCCESS {
@@ -95,5 +137,6 @@ impl Interface {
             }
             check_config(cr, CR_BUFFER_SMALL)?;
         }
+        Err(WinError::BufferTooSmall)
     }
 }
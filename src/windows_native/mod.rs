@@ -8,56 +8,110 @@ macro_rules! ensure {
     };
 }
 
+mod ble;
 mod descriptor;
 mod dev_node;
 mod device_info;
 mod error;
 mod hid;
+mod instance_id;
 mod interfaces;
 mod string;
 mod types;
+mod usb;
 mod utils;
 
 use std::cell::{Cell, RefCell};
+use std::mem::size_of;
 use std::ptr::{null, null_mut};
 use std::{
     ffi::CStr,
     fmt::{self, Debug},
+    panic, thread,
 };
 
 use crate::windows_native::dev_node::DevNode;
-use crate::windows_native::device_info::get_device_info;
-use crate::windows_native::error::{check_boolean, Win32Error, WinError, WinResult};
-use crate::windows_native::hid::{get_hid_attributes, PreparsedData};
+use crate::windows_native::device_info::{
+    get_bus, get_device_info, get_device_info_on_bus, get_minimal_device_info,
+};
+use crate::windows_native::error::{check_boolean, ResultExt, Win32Error, WinError, WinResult};
+use crate::windows_native::hid::{get_hid_attributes, try_get_hid_attributes, PreparsedData};
+pub use crate::windows_native::instance_id::UsbInstanceId;
 use crate::windows_native::interfaces::Interface;
 use crate::windows_native::string::{U16Str, U16String};
 use crate::windows_native::types::{Handle, Overlapped};
-use crate::{DeviceInfo, HidDeviceBackendBase, HidDeviceBackendWindows, HidError, HidResult};
+use crate::{
+    Access, AccessState, BusType, DeviceInfo, EnumStats, HidDeviceBackendBase,
+    HidDeviceBackendWindows, HidError, HidResult,
+};
 use windows_sys::core::GUID;
 use windows_sys::Win32::Devices::HumanInterfaceDevice::{
-    HidD_GetIndexedString, HidD_SetFeature, HidD_SetNumInputBuffers,
+    HidD_GetIndexedString, HidD_GetNumInputBuffers, HidD_SetFeature, HidD_SetNumInputBuffers,
+    HidD_SetOutputReport,
 };
 use windows_sys::Win32::Devices::Properties::{
     DEVPKEY_Device_ContainerId, DEVPKEY_Device_InstanceId,
 };
-use windows_sys::Win32::Foundation::{GENERIC_READ, GENERIC_WRITE, INVALID_HANDLE_VALUE, TRUE};
+use windows_sys::Win32::Foundation::{
+    ERROR_INVALID_FUNCTION, ERROR_NOT_FOUND, ERROR_NOT_SUPPORTED, ERROR_SHARING_VIOLATION,
+    GENERIC_READ, GENERIC_WRITE, HANDLE, INVALID_HANDLE_VALUE, TRUE,
+};
 use windows_sys::Win32::Storage::FileSystem::{
     CreateFileW, ReadFile, WriteFile, FILE_FLAG_OVERLAPPED, FILE_SHARE_READ, FILE_SHARE_WRITE,
     OPEN_EXISTING,
 };
 use windows_sys::Win32::System::Threading::ResetEvent;
-use windows_sys::Win32::System::IO::{CancelIo, DeviceIoControl};
+use windows_sys::Win32::System::IO::{CancelIo, CancelIoEx, DeviceIoControl};
 
 const STRING_BUF_LEN: usize = 128;
+/// Gathering the info of an interface is mostly waiting for its driver, so
+/// the interfaces are split between a few threads during enumeration
+const MAX_ENUMERATION_THREADS: usize = 4;
+/// Below this, starting a thread takes longer than enumerating the interfaces
+const MIN_INTERFACES_PER_THREAD: usize = 8;
+/// Enumerating only fails if the list of device interfaces can not be fetched
+const ENUMERATE_CONTEXT: &str = "CM_Get_Device_Interface_ListW";
 
 pub struct HidApiBackend;
 impl HidApiBackend {
     pub fn get_hid_device_info_vector(vid: u16, pid: u16) -> HidResult<Vec<DeviceInfo>> {
-        Ok(enumerate_devices(vid, pid)?)
+        enumerate_devices(vid, pid).context(ENUMERATE_CONTEXT)
+    }
+
+    pub fn enumerate_with(vid: u16, pid: u16, f: impl FnMut(DeviceInfo)) -> HidResult<()> {
+        for_each_device(vid, pid, f).context(ENUMERATE_CONTEXT)
+    }
+
+    pub fn get_hid_device_info_vector_for_bus(bus: BusType) -> HidResult<Vec<DeviceInfo>> {
+        enumerate_devices_on_bus(bus).context(ENUMERATE_CONTEXT)
+    }
+
+    pub fn get_hid_device_info_vector_all() -> HidResult<Vec<DeviceInfo>> {
+        enumerate_all_devices().context(ENUMERATE_CONTEXT)
     }
 
-    pub fn open(vid: u16, pid: u16) -> HidResult<HidDevice> {
-        open(vid, pid, None)
+    pub fn get_hid_device_info_vector_with_stats(
+        vid: u16,
+        pid: u16,
+    ) -> HidResult<(Vec<DeviceInfo>, EnumStats)> {
+        let mut devices = Vec::new();
+        let stats = for_each_device_with_stats(vid, pid, |info| devices.push(info))
+            .context(ENUMERATE_CONTEXT)?;
+        Ok((devices, stats))
+    }
+
+    pub fn get_hid_device_info_vector_with_access(
+        vid: u16,
+        pid: u16,
+    ) -> HidResult<Vec<(DeviceInfo, AccessState)>> {
+        enumerate_devices_with_access(vid, pid).context(ENUMERATE_CONTEXT)
+    }
+
+    pub fn get_ble_battery_level(device_path: &CStr) -> HidResult<Option<u8>> {
+        let device_path = U16String::try_from(device_path).map_err(|_| HidError::HidApiError {
+            message: "device path is not valid unicode".into(),
+        })?;
+        Ok(ble::battery_level(&device_path)?)
     }
 
     pub fn open_serial(vid: u16, pid: u16, sn: &str) -> HidResult<HidDevice> {
@@ -67,6 +121,10 @@ impl HidApiBackend {
     pub fn open_path(device_path: &CStr) -> HidResult<HidDevice> {
         open_path(device_path)
     }
+
+    pub fn open_path_with_access(device_path: &CStr, access: Access) -> HidResult<HidDevice> {
+        open_path_with_access(device_path, access)
+    }
 }
 
 /// Object for accessing HID device
@@ -120,6 +178,70 @@ impl AsyncState {
     }
 }
 
+impl HidDevice {
+    /// Cancel the pending transfers and close the handle, unless it is closed already.
+    ///
+    /// Used by both `close()` and `Drop`, which ignores the error.
+    fn close_handle(&mut self) -> WinResult<()> {
+        // A closed handle is replaced by this, which no open device has
+        if self.device_handle.as_raw() == INVALID_HANDLE_VALUE {
+            return Ok(());
+        }
+        unsafe {
+            CancelIo(self.device_handle.as_raw());
+        }
+        let handle = std::mem::replace(
+            &mut self.device_handle,
+            Handle::from_raw(INVALID_HANDLE_VALUE),
+        );
+        handle.close()
+    }
+
+    /// Read the string descriptor `index` in the language `lang_id`, 0 selects the default language.
+    fn read_string_descriptor(&self, index: u8, lang_id: u16) -> HidResult<[u16; STRING_BUF_LEN]> {
+        let mut buf = [0u16; STRING_BUF_LEN];
+        // IOCTL_HID_GET_INDEXED_STRING takes the index in the low word and the
+        // language in the high word
+        let res = unsafe {
+            HidD_GetIndexedString(
+                self.device_handle.as_raw(),
+                (lang_id as u32) << 16 | index as u32,
+                buf.as_mut_ptr() as _,
+                (STRING_BUF_LEN * size_of::<u16>()) as u32,
+            )
+        };
+        check_boolean(res)?;
+        Ok(buf)
+    }
+
+    /// Issue an overlapped read into the read buffer, unless one is still pending.
+    fn start_read(&self, state: &mut AsyncState) -> HidResult<()> {
+        if self.read_pending.get() {
+            return Ok(());
+        }
+        state.clear_buffer();
+        let res = unsafe {
+            ResetEvent(state.overlapped.event_handle());
+            ReadFile(
+                self.device_handle.as_raw(),
+                state.buffer_ptr() as _,
+                state.buffer_len() as u32,
+                null_mut(),
+                state.overlapped.as_raw(),
+            )
+        };
+        if res != TRUE {
+            let err = Win32Error::last();
+            if err != Win32Error::IoPending {
+                unsafe { CancelIo(self.device_handle.as_raw()) };
+                return Err(err.into());
+            }
+        }
+        self.read_pending.set(true);
+        Ok(())
+    }
+}
+
 impl Debug for HidDevice {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("HidDevice").finish()
@@ -147,7 +269,8 @@ impl HidDeviceBackendBase for HidDevice {
             ensure!(err == Win32Error::IoPending, Err(err.into()));
             Ok(state
                 .overlapped
-                .get_result(&self.device_handle, Some(1000))?)
+                .get_result(&self.device_handle, Some(1000))
+                .map_err(WinError::into_transfer_error)?)
         } else {
             Ok(0)
         }
@@ -159,50 +282,23 @@ impl HidDeviceBackendBase for HidDevice {
 
     fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> HidResult<usize> {
         ensure!(!buf.is_empty(), Err(HidError::InvalidZeroSizeData));
-        let mut bytes_read = 0;
-        let mut io_runnig = false;
         let mut state = self.read_state.borrow_mut();
-
-        if !self.read_pending.get() {
-            self.read_pending.set(true);
-            state.clear_buffer();
-            let res = unsafe {
-                ResetEvent(state.overlapped.event_handle());
-                ReadFile(
-                    self.device_handle.as_raw(),
-                    state.buffer_ptr() as _,
-                    state.buffer_len() as u32,
-                    &mut bytes_read,
-                    state.overlapped.as_raw(),
-                )
-            };
-            if res != TRUE {
-                let err = Win32Error::last();
-                if err != Win32Error::IoPending {
-                    unsafe { CancelIo(self.device_handle.as_raw()) };
-                    self.read_pending.set(false);
-                    return Err(err.into());
-                }
-                io_runnig = true;
+        self.start_read(&mut state)?;
+
+        // The event is also signaled if ReadFile finished right away, so the
+        // result of a read is always collected from the overlapped structure
+        let res = state
+            .overlapped
+            .get_result(&self.device_handle, u32::try_from(timeout).ok());
+        let mut bytes_read = match res {
+            Ok(written) => written as u32,
+            //There was no data this time. Return zero bytes available, but leave the Overlapped I/O running.
+            Err(WinError::WaitTimedOut) => return Ok(0),
+            Err(err) => {
+                self.read_pending.set(false);
+                return Err(err.into_transfer_error().into());
             }
-        } else {
-            io_runnig = true;
-        }
-
-        if io_runnig {
-            let res = state
-                .overlapped
-                .get_result(&self.device_handle, u32::try_from(timeout).ok());
-            bytes_read = match res {
-                Ok(written) => written as u32,
-                //There was no data this time. Return zero bytes available, but leave the Overlapped I/O running.
-                Err(WinError::WaitTimedOut) => return Ok(0),
-                Err(err) => {
-                    self.read_pending.set(false);
-                    return Err(err.into());
-                }
-            };
-        }
+        };
         self.read_pending.set(false);
 
         let mut copy_len = 0;
@@ -239,41 +335,28 @@ impl HidDeviceBackendBase for HidDevice {
         Ok(())
     }
 
-    /// Set the first byte of `buf` to the 'Report ID' of the report to be read.
-    /// Upon return, the first byte will still contain the Report ID, and the
-    /// report data will start in `buf[1]`.
-    fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
-        #[allow(clippy::identity_op, clippy::double_parens)]
-        const IOCTL_HID_GET_FEATURE: u32 = ((0x0000000b) << 16) | ((0) << 14) | ((100) << 2) | (2);
-        ensure!(!buf.is_empty(), Err(HidError::InvalidZeroSizeData));
-        let mut state = self.feature_state.borrow_mut();
-        let mut bytes_returned = 0;
+    fn send_output_report(&self, data: &[u8]) -> HidResult<usize> {
+        ensure!(!data.is_empty(), Err(HidError::InvalidZeroSizeData));
+        // Output reports share the buffer with write, which is sized for them
+        let mut state = self.write_state.borrow_mut();
+        state.fill_buffer(data);
 
-        let res = unsafe {
-            ResetEvent(state.overlapped.event_handle());
-            DeviceIoControl(
+        check_boolean(unsafe {
+            HidD_SetOutputReport(
                 self.device_handle.as_raw(),
-                IOCTL_HID_GET_FEATURE,
-                buf.as_mut_ptr() as _,
-                buf.len() as u32,
-                buf.as_mut_ptr() as _,
-                buf.len() as u32,
-                &mut bytes_returned,
-                state.overlapped.as_raw(),
+                state.buffer_ptr() as _,
+                state.buffer_len() as u32,
             )
-        };
-        if res != TRUE {
-            let err = Win32Error::last();
-            ensure!(err == Win32Error::IoPending, Err(err.into()))
-        }
-
-        bytes_returned = state.overlapped.get_result(&self.device_handle, None)? as u32;
+        })?;
 
-        if buf[0] == 0x0 {
-            bytes_returned += 1;
-        }
+        Ok(data.len().min(state.buffer_len()))
+    }
 
-        Ok(bytes_returned as usize)
+    /// Set the first byte of `buf` to the 'Report ID' of the report to be read.
+    /// Upon return, the first byte will still contain the Report ID, and the
+    /// report data will start in `buf[1]`.
+    fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        self.get_feature_report_timeout(buf, -1)
     }
 
     fn set_blocking_mode(&self, blocking: bool) -> HidResult<()> {
@@ -294,19 +377,23 @@ impl HidDeviceBackendBase for HidDevice {
     }
 
     fn get_indexed_string(&self, index: i32) -> HidResult<Option<String>> {
-        let mut buf = [0u16; STRING_BUF_LEN];
-        let res = unsafe {
-            HidD_GetIndexedString(
-                self.device_handle.as_raw(),
-                index as u32,
-                buf.as_mut_ptr() as _,
-                STRING_BUF_LEN as u32,
-            )
-        };
-        check_boolean(res)?;
+        self.get_indexed_string_lang(index, 0)
+    }
+
+    fn get_indexed_string_lang(&self, index: i32, lang_id: u16) -> HidResult<Option<String>> {
+        let buf = self.read_string_descriptor(index as u8, lang_id)?;
         Ok(buf.split(|c| *c == 0).map(String::from_utf16_lossy).next())
     }
 
+    fn get_supported_languages(&self) -> HidResult<Vec<u16>> {
+        // String descriptor 0 holds the LANGIDs instead of a string
+        let buf = self.read_string_descriptor(0, 0)?;
+        Ok(buf
+            .into_iter()
+            .take_while(|&lang_id| lang_id != 0)
+            .collect())
+    }
+
     fn get_device_info(&self) -> HidResult<DeviceInfo> {
         Ok(self.device_info.clone())
     }
@@ -317,6 +404,51 @@ impl HidDeviceBackendBase for HidDevice {
         buf[..size].copy_from_slice(&desc[..size]);
         Ok(size)
     }
+
+    fn get_report_descriptor_size(&self) -> HidResult<usize> {
+        // The descriptor is rebuilt from the preparsed data, so its size is only known afterwards
+        let desc = descriptor::get_descriptor(&PreparsedData::load(&self.device_handle)?)?;
+        Ok(desc.len())
+    }
+
+    fn is_connected(&self) -> bool {
+        // The attributes are kept by the HID class driver, so this does not reach the device
+        try_get_hid_attributes(&self.device_handle).is_ok()
+    }
+
+    fn poll_readable(&self, timeout: i32) -> HidResult<bool> {
+        let mut state = self.read_state.borrow_mut();
+        self.start_read(&mut state)?;
+
+        // Waiting for the result leaves the read pending, so the next read
+        // collects the report from the read buffer
+        match state
+            .overlapped
+            .get_result(&self.device_handle, u32::try_from(timeout).ok())
+        {
+            Ok(_) => Ok(true),
+            Err(WinError::WaitTimedOut) => Ok(false),
+            Err(err) => {
+                self.read_pending.set(false);
+                Err(err.into_transfer_error().into())
+            }
+        }
+    }
+
+    fn cancel_io(&self) -> HidResult<()> {
+        let res = unsafe { CancelIoEx(self.device_handle.as_raw(), null()) };
+        if res != TRUE {
+            let err = Win32Error::last();
+            // There was no pending I/O to cancel
+            ensure!(err == Win32Error::Generic(ERROR_NOT_FOUND), Err(err.into()));
+        }
+        Ok(())
+    }
+
+    fn close(mut self: Box<Self>) -> HidResult<()> {
+        self.close_handle()?;
+        Ok(())
+    }
 }
 
 impl HidDeviceBackendWindows for HidDevice {
@@ -330,37 +462,330 @@ impl HidDeviceBackendWindows for HidDevice {
         let guid = dev_node.get_property(DEVPKEY_Device_ContainerId)?;
         Ok(guid)
     }
+
+    fn set_input_buffer_count(&self, count: u32) -> HidResult<()> {
+        check_boolean(unsafe { HidD_SetNumInputBuffers(self.device_handle.as_raw(), count) })?;
+        Ok(())
+    }
+
+    fn get_physical_descriptor(&self, buf: &mut [u8]) -> HidResult<usize> {
+        // This is what HidD_GetPhysicalDescriptor does internally, but it also reports the length
+        #[allow(clippy::identity_op, clippy::double_parens)]
+        const IOCTL_GET_PHYSICAL_DESCRIPTOR: u32 =
+            ((0x0000000b) << 16) | ((0) << 14) | ((102) << 2) | (2);
+        ensure!(!buf.is_empty(), Err(HidError::InvalidZeroSizeData));
+        let mut state = self.feature_state.borrow_mut();
+        let mut bytes_returned = 0;
+
+        let res = unsafe {
+            ResetEvent(state.overlapped.event_handle());
+            DeviceIoControl(
+                self.device_handle.as_raw(),
+                IOCTL_GET_PHYSICAL_DESCRIPTOR,
+                null(),
+                0,
+                buf.as_mut_ptr() as _,
+                buf.len() as u32,
+                &mut bytes_returned,
+                state.overlapped.as_raw(),
+            )
+        };
+        if res != TRUE {
+            let err = Win32Error::last();
+            ensure!(
+                err == Win32Error::IoPending,
+                Err(physical_descriptor_error(err))
+            )
+        }
+
+        match state.overlapped.get_result(&self.device_handle, None) {
+            Ok(bytes_returned) => Ok(bytes_returned),
+            Err(WinError::Win32(err)) => Err(physical_descriptor_error(err)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn request_reset(&self) -> HidResult<()> {
+        ensure!(
+            self.device_info.bus_type == BusType::Usb,
+            Err(HidError::UnsupportedOperation {
+                operation: "request_reset",
+            })
+        );
+        let path =
+            U16String::try_from(self.device_info.path()).expect("device path is not valid unicode");
+        let device_id: U16String = Interface::get_property(&path, DEVPKEY_Device_InstanceId)?;
+        usb::cycle_port(DevNode::from_device_id(&device_id)?)?;
+        Ok(())
+    }
+
+    fn get_input_buffer_count(&self) -> HidResult<u32> {
+        let mut count = 0;
+        check_boolean(unsafe { HidD_GetNumInputBuffers(self.device_handle.as_raw(), &mut count) })?;
+        Ok(count)
+    }
+
+    fn begin_read(&self) -> HidResult<HANDLE> {
+        let mut state = self.read_state.borrow_mut();
+        self.start_read(&mut state)?;
+        Ok(state.overlapped.event_handle())
+    }
+
+    fn finish_read(&self, buf: &mut [u8]) -> HidResult<usize> {
+        // The read was started by `begin_read`, so this only collects its result
+        self.read_timeout(buf, 0)
+    }
+
+    fn get_feature_report_timeout(&self, buf: &mut [u8], timeout: i32) -> HidResult<usize> {
+        #[allow(clippy::identity_op, clippy::double_parens)]
+        const IOCTL_HID_GET_FEATURE: u32 = ((0x0000000b) << 16) | ((0) << 14) | ((100) << 2) | (2);
+        ensure!(!buf.is_empty(), Err(HidError::InvalidZeroSizeData));
+        let mut state = self.feature_state.borrow_mut();
+        let mut bytes_returned = 0;
+
+        let res = unsafe {
+            ResetEvent(state.overlapped.event_handle());
+            DeviceIoControl(
+                self.device_handle.as_raw(),
+                IOCTL_HID_GET_FEATURE,
+                buf.as_mut_ptr() as _,
+                buf.len() as u32,
+                buf.as_mut_ptr() as _,
+                buf.len() as u32,
+                &mut bytes_returned,
+                state.overlapped.as_raw(),
+            )
+        };
+        if res != TRUE {
+            let err = Win32Error::last();
+            ensure!(err == Win32Error::IoPending, Err(err.into()))
+        }
+
+        let res = state
+            .overlapped
+            .get_result(&self.device_handle, u32::try_from(timeout).ok());
+        bytes_returned = match res {
+            Ok(bytes_returned) => bytes_returned as u32,
+            Err(WinError::WaitTimedOut) => {
+                // The driver writes into `buf`, so the request has to be finished
+                // before returning, even though its result is not used anymore
+                unsafe { CancelIoEx(self.device_handle.as_raw(), state.overlapped.as_raw()) };
+                let _ = state.overlapped.get_result(&self.device_handle, None);
+                return Err(HidError::Timeout);
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        if buf[0] == 0x0 {
+            bytes_returned += 1;
+        }
+
+        Ok(bytes_returned as usize)
+    }
+
+    fn input_report_length(&self) -> HidResult<usize> {
+        let caps = PreparsedData::load(&self.device_handle)?.get_caps()?;
+        Ok(caps.InputReportByteLength as usize)
+    }
+
+    fn output_report_length(&self) -> HidResult<usize> {
+        let caps = PreparsedData::load(&self.device_handle)?.get_caps()?;
+        Ok(caps.OutputReportByteLength as usize)
+    }
+
+    fn feature_report_length(&self) -> HidResult<usize> {
+        let caps = PreparsedData::load(&self.device_handle)?.get_caps()?;
+        Ok(caps.FeatureReportByteLength as usize)
+    }
 }
 
 impl Drop for HidDevice {
     fn drop(&mut self) {
-        unsafe {
-            CancelIo(self.device_handle.as_raw());
+        let _ = self.close_handle();
+    }
+}
+
+/// The HID class driver rejects the request if the device has no physical descriptor
+fn physical_descriptor_error(err: Win32Error) -> HidError {
+    match err {
+        Win32Error::Generic(ERROR_INVALID_FUNCTION | ERROR_NOT_FOUND | ERROR_NOT_SUPPORTED) => {
+            HidError::UnsupportedOperation {
+                operation: "get_physical_descriptor",
+            }
         }
+        err => err.into(),
     }
 }
 
 fn enumerate_devices(vendor_id: u16, product_id: u16) -> WinResult<Vec<DeviceInfo>> {
+    let mut devices = Vec::new();
+    for_each_device(vendor_id, product_id, |info| devices.push(info))?;
+    Ok(devices)
+}
+
+fn for_each_device(vendor_id: u16, product_id: u16, f: impl FnMut(DeviceInfo)) -> WinResult<()> {
+    for_each_device_with_stats(vendor_id, product_id, f).map(|_| ())
+}
+
+fn for_each_device_with_stats(
+    vendor_id: u16,
+    product_id: u16,
+    mut f: impl FnMut(DeviceInfo),
+) -> WinResult<EnumStats> {
+    let interfaces = Interface::get_interface_list()?;
+    let interfaces: Vec<&U16Str> = interfaces.iter().collect();
+    let mut stats = EnumStats::default();
+    map_interfaces(
+        &interfaces,
+        |device_interface| {
+            open_device(device_interface, false).map(|device_handle| {
+                match_device_info(device_interface, &device_handle, vendor_id, product_id)
+            })
+        },
+        |result| {
+            stats.total_interfaces += 1;
+            match result {
+                Ok(Some(info)) => f(info),
+                Ok(None) => (),
+                // Devices which can not be opened are left out, usually because access was denied
+                Err(_) => stats.skipped_open_failures += 1,
+            }
+        },
+    );
+    Ok(stats)
+}
+
+/// Apply `map` to every interface, spread over a few threads, and pass the
+/// results to `f` in the order of `interfaces`.
+fn map_interfaces<T: Send>(
+    interfaces: &[&U16Str],
+    map: impl Fn(&U16Str) -> T + Sync,
+    mut f: impl FnMut(T),
+) {
+    let threads = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_ENUMERATION_THREADS);
+    let chunk_size = interfaces
+        .len()
+        .div_ceil(threads)
+        .max(MIN_INTERFACES_PER_THREAD);
+    let mut chunks = interfaces.chunks(chunk_size);
+    let Some(first) = chunks.next() else {
+        return;
+    };
+    let map = &map;
+    thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .map(|chunk| scope.spawn(move || chunk.iter().map(|&i| map(i)).collect::<Vec<_>>()))
+            .collect();
+        // The first chunk runs on this thread, so that its results are passed
+        // on right away instead of after all threads are done
+        first.iter().for_each(|&i| f(map(i)));
+        for handle in handles {
+            let results = handle.join().unwrap_or_else(|e| panic::resume_unwind(e));
+            results.into_iter().for_each(&mut f);
+        }
+    });
+}
+
+fn enumerate_devices_on_bus(bus: BusType) -> WinResult<Vec<DeviceInfo>> {
     Ok(Interface::get_interface_list()?
         .iter()
         .filter_map(|device_interface| {
+            // Classify the bus first, so that devices on other buses are never opened
+            let bus_info = get_bus(device_interface);
+            let bus_type = bus_info
+                .as_ref()
+                .map_or(BusType::Unknown, |(bus_type, _)| (*bus_type).into());
+            if bus_type != bus {
+                return None;
+            }
             let device_handle = open_device(device_interface, false).ok()?;
-            let attrib = get_hid_attributes(&device_handle);
-            ((vendor_id == 0 || attrib.VendorID == vendor_id)
-                && (product_id == 0 || attrib.ProductID == product_id))
-                .then(|| get_device_info(device_interface, &device_handle))
+            get_device_info_on_bus(device_interface, &device_handle, bus_info).ok()
+        })
+        .collect())
+}
+
+fn enumerate_all_devices() -> WinResult<Vec<DeviceInfo>> {
+    let present = Interface::get_interface_list()?;
+    let is_present = |device_interface: &U16Str| {
+        present
+            .iter()
+            .any(|other| other.as_slice() == device_interface.as_slice())
+    };
+    Ok(Interface::get_all_interface_list()?
+        .iter()
+        .filter_map(|device_interface| {
+            match open_device(device_interface, false) {
+                Ok(device_handle) => get_device_info(device_interface, &device_handle),
+                // Devices which are not connected can not be opened
+                Err(_) => get_minimal_device_info(device_interface, is_present(device_interface)),
+            }
+            .ok()
+        })
+        .collect())
+}
+
+fn enumerate_devices_with_access(
+    vendor_id: u16,
+    product_id: u16,
+) -> WinResult<Vec<(DeviceInfo, AccessState)>> {
+    Ok(Interface::get_interface_list()?
+        .iter()
+        .filter_map(|device_interface| {
+            let info = get_matching_device_info(device_interface, vendor_id, product_id)?;
+            Some((info, get_access_state(device_interface)))
         })
         .collect())
 }
 
+fn get_matching_device_info(
+    device_interface: &U16Str,
+    vendor_id: u16,
+    product_id: u16,
+) -> Option<DeviceInfo> {
+    let device_handle = open_device(device_interface, false).ok()?;
+    match_device_info(device_interface, &device_handle, vendor_id, product_id)
+}
+
+fn match_device_info(
+    device_interface: &U16Str,
+    device_handle: &Handle,
+    vendor_id: u16,
+    product_id: u16,
+) -> Option<DeviceInfo> {
+    let attrib = get_hid_attributes(device_handle);
+    ((vendor_id == 0 || attrib.VendorID == vendor_id)
+        && (product_id == 0 || attrib.ProductID == product_id))
+        .then(|| get_device_info(device_interface, device_handle))?
+        // Skip devices whose path can not be represented as a CString
+        .ok()
+}
+
+fn get_access_state(device_interface: &U16Str) -> AccessState {
+    // Another process holding the device exclusively makes a read-write open fail
+    // with a sharing violation. Any other failure is not caused by someone else.
+    match open_device(device_interface, true) {
+        Err(WinError::Win32(Win32Error::Generic(ERROR_SHARING_VIOLATION))) => AccessState::Busy,
+        _ => AccessState::Available,
+    }
+}
+
 fn open_device(path: &U16Str, open_rw: bool) -> WinResult<Handle> {
+    create_device_handle(
+        path,
+        match open_rw {
+            true => GENERIC_WRITE | GENERIC_READ,
+            false => 0,
+        },
+    )
+}
+
+fn create_device_handle(path: &U16Str, desired_access: u32) -> WinResult<Handle> {
     let handle = unsafe {
         CreateFileW(
             path.as_ptr(),
-            match open_rw {
-                true => GENERIC_WRITE | GENERIC_READ,
-                false => 0,
-            },
+            desired_access,
             FILE_SHARE_READ | FILE_SHARE_WRITE,
             null(),
             OPEN_EXISTING,
@@ -376,11 +801,12 @@ fn open_device(path: &U16Str, open_rw: bool) -> WinResult<Handle> {
 }
 
 fn open(vid: u16, pid: u16, sn: Option<&str>) -> HidResult<HidDevice> {
-    let dev = enumerate_devices(vid, pid)?
+    let dev = enumerate_devices(vid, pid)
+        .context(ENUMERATE_CONTEXT)?
         .into_iter()
         .filter(|dev| dev.vendor_id == vid && dev.product_id == pid)
-        .find(|dev| sn.map_or(true, |sn| dev.serial_number().is_some_and(|n| sn == n)))
-        .ok_or(HidError::HidApiErrorEmpty)?;
+        .find(|dev| sn.map_or(true, |sn| dev.serial_number.eq_str(sn)))
+        .ok_or(HidError::DeviceNotFound)?;
     open_path(dev.path())
 }
 
@@ -392,10 +818,31 @@ fn open_path(device_path: &CStr) -> HidResult<HidDevice> {
         // them.  This is to prevent keyloggers.  However, feature reports
         // can still be sent and received.  Retry opening the device, but
         // without read/write access.
-        .or_else(|_| open_device(&device_path, false))?;
-    check_boolean(unsafe { HidD_SetNumInputBuffers(handle.as_raw(), 64) })?;
-    let caps = PreparsedData::load(&handle)?.get_caps()?;
-    let device_info = get_device_info(&device_path, &handle);
+        .or_else(|_| open_device(&device_path, false))
+        .context("CreateFileW")?;
+    open_handle(&device_path, handle)
+}
+
+fn open_path_with_access(device_path: &CStr, access: Access) -> HidResult<HidDevice> {
+    let device_path = U16String::try_from(device_path).unwrap();
+    let desired_access = match access {
+        Access::Read => GENERIC_READ,
+        Access::Write => GENERIC_WRITE,
+        Access::ReadWrite => GENERIC_READ | GENERIC_WRITE,
+    };
+    // A denied access turns into a `Backend` error of kind `PermissionDenied`
+    let handle = create_device_handle(&device_path, desired_access).context("CreateFileW")?;
+    open_handle(&device_path, handle)
+}
+
+fn open_handle(device_path: &U16Str, handle: Handle) -> HidResult<HidDevice> {
+    check_boolean(unsafe { HidD_SetNumInputBuffers(handle.as_raw(), 64) })
+        .context("HidD_SetNumInputBuffers")?;
+    let caps = PreparsedData::load(&handle)
+        .context("HidD_GetPreparsedData")?
+        .get_caps()
+        .context("HidP_GetCaps")?;
+    let device_info = get_device_info(device_path, &handle)?;
     let dev = HidDevice {
         device_handle: handle,
         blocking: Cell::new(true),
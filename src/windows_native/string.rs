@@ -38,27 +38,10 @@ impl U16Str {
         unsafe { Self::from_slice_unsafe(slice) }
     }
 
-    pub fn from_slice_mut(slice: &mut [u16]) -> &mut Self {
-        assert!(
-            slice.last().is_some_and(is_null),
-            "Slice is not null terminated"
-        );
-        debug_assert_eq!(
-            slice.iter().filter(|c| is_null(c)).count(),
-            1,
-            "Found null character in the middle"
-        );
-        unsafe { Self::from_slice_mut_unsafe(slice) }
-    }
-
     pub fn from_slice_list(slice: &[u16]) -> impl Iterator<Item = &U16Str> {
         slice.split_inclusive(is_null).map(Self::from_slice)
     }
 
-    pub fn from_slice_list_mut(slice: &mut [u16]) -> impl Iterator<Item = &mut U16Str> {
-        slice.split_inclusive_mut(is_null).map(Self::from_slice_mut)
-    }
-
     pub fn as_ptr(&self) -> PCWSTR {
         self.0.as_ptr()
     }
@@ -66,38 +49,14 @@ impl U16Str {
     pub fn as_slice(&self) -> &[u16] {
         &self.0[..self.0.len() - 1]
     }
-    pub fn as_slice_mut(&mut self) -> &mut [u16] {
-        let end = self.0.len() - 1;
-        &mut self.0[..end]
-    }
-
-    pub fn make_uppercase_ascii(&mut self) {
-        for c in self.as_slice_mut() {
-            if let Ok(t) = u8::try_from(*c) {
-                *c = t.to_ascii_uppercase().into();
-            }
-        }
-    }
 
     pub fn starts_with_ignore_case(&self, pattern: &str) -> bool {
-        char::decode_utf16(self.as_slice().iter().copied())
-            .map(|r| r.unwrap_or(char::REPLACEMENT_CHARACTER))
-            .zip(pattern.chars())
-            .all(|(l, r)| l.eq_ignore_ascii_case(&r))
-    }
-
-    pub fn find_index(&self, pattern: &str) -> Option<usize> {
-        self.as_slice()
-            .windows(pattern.encode_utf16().count())
-            .enumerate()
-            .filter(|(_, ss)| {
-                ss.iter()
-                    .copied()
-                    .zip(pattern.encode_utf16())
-                    .all(|(l, r)| l == r)
-            })
-            .map(|(i, _)| i)
-            .next()
+        let mut chars = char::decode_utf16(self.as_slice().iter().copied())
+            .map(|r| r.unwrap_or(char::REPLACEMENT_CHARACTER));
+        // A string which is shorter than the pattern runs out of chars and does not match
+        pattern
+            .chars()
+            .all(|r| chars.next().is_some_and(|l| l.eq_ignore_ascii_case(&r)))
     }
 }
 
@@ -156,6 +115,12 @@ impl TryFrom<&CStr> for U16String {
     }
 }
 
+impl From<&U16Str> for U16String {
+    fn from(value: &U16Str) -> Self {
+        Self(value.0.to_vec())
+    }
+}
+
 impl From<U16String> for WcharString {
     fn from(value: U16String) -> Self {
         (&*value).into()
@@ -213,11 +178,33 @@ impl U16StringList {
     pub fn iter(&self) -> impl Iterator<Item = &U16Str> {
         U16Str::from_slice_list(self.0.as_slice())
     }
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut U16Str> {
-        U16Str::from_slice_list_mut(self.0.as_mut_slice())
-    }
 }
 
 fn is_null(c: &u16) -> bool {
     *c == 0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(once(0)).collect()
+    }
+
+    #[test]
+    fn test_starts_with_ignore_case() {
+        let usb = wide("usb\\Class_03");
+        assert!(U16Str::from_slice(&usb).starts_with_ignore_case("USB"));
+        assert!(!U16Str::from_slice(&usb).starts_with_ignore_case("BTHENUM"));
+    }
+
+    #[test]
+    fn test_starts_with_ignore_case_shorter_string() {
+        let us = wide("US");
+        assert!(!U16Str::from_slice(&us).starts_with_ignore_case("USB"));
+        let empty = wide("");
+        assert!(!U16Str::from_slice(&empty).starts_with_ignore_case("USB"));
+        assert!(U16Str::from_slice(&empty).starts_with_ignore_case(""));
+    }
+}
@@ -3,7 +3,9 @@ use crate::BusType;
 use std::mem::{size_of, zeroed};
 use std::ptr::null;
 use windows_sys::core::GUID;
-use windows_sys::Win32::Devices::Properties::{DEVPROPKEY, DEVPROPTYPE, DEVPROP_TYPE_GUID};
+use windows_sys::Win32::Devices::Properties::{
+    DEVPROPKEY, DEVPROPTYPE, DEVPROP_TYPE_GUID, DEVPROP_TYPE_UINT32,
+};
 use windows_sys::Win32::Foundation::{CloseHandle, FALSE, HANDLE, INVALID_HANDLE_VALUE, TRUE};
 use windows_sys::Win32::System::Threading::{CreateEventW, INFINITE};
 use windows_sys::Win32::System::IO::{GetOverlappedResultEx, OVERLAPPED};
@@ -30,6 +32,19 @@ unsafe impl DeviceProperty for GUID {
     }
 }
 
+unsafe impl DeviceProperty for u32 {
+    const TYPE: DEVPROPTYPE = DEVPROP_TYPE_UINT32;
+
+    fn create_sized(bytes: usize) -> Self {
+        assert_eq!(bytes, size_of::<u32>());
+        0
+    }
+
+    fn as_ptr_mut(&mut self) -> *mut u8 {
+        (self as *mut u32) as *mut u8
+    }
+}
+
 pub trait PropertyKey: Copy {
     fn as_ptr(&self) -> *const DEVPROPKEY;
 }
@@ -62,7 +77,7 @@ impl From<InternalBusType> for BusType {
             InternalBusType::Unknown => BusType::Unknown,
             InternalBusType::Usb => BusType::Usb,
             InternalBusType::Bluetooth => BusType::Bluetooth,
-            InternalBusType::BluetoothLE => BusType::Bluetooth,
+            InternalBusType::BluetoothLE => BusType::BluetoothLE,
             InternalBusType::I2c => BusType::I2c,
             InternalBusType::Spi => BusType::Spi,
         }
@@ -78,6 +93,14 @@ impl Handle {
     pub fn as_raw(&self) -> HANDLE {
         self.0
     }
+    pub fn close(mut self) -> WinResult<()> {
+        let handle = std::mem::replace(&mut self.0, INVALID_HANDLE_VALUE);
+        ensure!(
+            unsafe { CloseHandle(handle) } != FALSE,
+            Err(WinError::last())
+        );
+        Ok(())
+    }
 }
 
 impl Drop for Handle {
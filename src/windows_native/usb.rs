@@ -0,0 +1,69 @@
+use crate::windows_native::dev_node::DevNode;
+use crate::windows_native::error::{Win32Error, WinError, WinResult};
+use crate::windows_native::interfaces::Interface;
+use crate::windows_native::string::U16String;
+use crate::windows_native::types::Overlapped;
+use std::mem::size_of;
+use windows_sys::Win32::Devices::Properties::{DEVPKEY_Device_Address, DEVPKEY_Device_InstanceId};
+use windows_sys::Win32::Devices::Usb::{
+    GUID_DEVINTERFACE_USB_HUB, IOCTL_USB_HUB_CYCLE_PORT, USB_CYCLE_PORT_PARAMS,
+};
+use windows_sys::Win32::Foundation::TRUE;
+use windows_sys::Win32::System::IO::DeviceIoControl;
+
+/// The HID collection sits below the USB interface, which sits below the USB device
+/// for composite devices. XUSB controllers add another level.
+const MAX_HUB_DISTANCE: usize = 4;
+
+/// Find the dev node of the USB hub above `dev_node` and the child of the hub,
+/// which is the USB device plugged into the hub's port.
+fn find_hub(mut dev_node: DevNode) -> WinResult<(U16String, DevNode)> {
+    for _ in 0..MAX_HUB_DISTANCE {
+        let parent = dev_node.parent()?;
+        let parent_id: U16String = parent.get_property(DEVPKEY_Device_InstanceId)?;
+        let hub_interfaces =
+            Interface::get_interface_list_of(GUID_DEVINTERFACE_USB_HUB, Some(&parent_id))?;
+        if let Some(hub_interface) = hub_interfaces.iter().next() {
+            return Ok((hub_interface.into(), dev_node));
+        }
+        dev_node = parent;
+    }
+    Err(WinError::InvalidDeviceNode)
+}
+
+/// Power cycle the hub port the USB device of the HID collection `dev_node` is plugged into.
+///
+/// The device disconnects and is enumerated again afterwards.
+pub fn cycle_port(dev_node: DevNode) -> WinResult<()> {
+    let (hub_interface, usb_device) = find_hub(dev_node)?;
+    // For devices attached to a hub, the address is the number of the port
+    let port: u32 = usb_device.get_property(DEVPKEY_Device_Address)?;
+
+    let hub = super::open_device(&hub_interface, true)?;
+    let mut params = USB_CYCLE_PORT_PARAMS {
+        ConnectionIndex: port,
+        StatusReturned: 0,
+    };
+    let mut overlapped = Overlapped::default();
+    let mut bytes_returned = 0;
+    // The same buffer is used for input and output, so both pointers must come from one borrow
+    let p = &mut params as *mut USB_CYCLE_PORT_PARAMS;
+    let res = unsafe {
+        DeviceIoControl(
+            hub.as_raw(),
+            IOCTL_USB_HUB_CYCLE_PORT,
+            p as _,
+            size_of::<USB_CYCLE_PORT_PARAMS>() as u32,
+            p as _,
+            size_of::<USB_CYCLE_PORT_PARAMS>() as u32,
+            &mut bytes_returned,
+            overlapped.as_raw(),
+        )
+    };
+    if res != TRUE {
+        let err = Win32Error::last();
+        ensure!(err == Win32Error::IoPending, Err(err.into()));
+    }
+    overlapped.get_result(&hub, None)?;
+    Ok(())
+}